@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use configure::{error::AppError, features::FlagRule, maintenance::MaintenanceStatus};
+use middleware::{
+    ctx::AdminUser,
+    extract::{Json, Path},
+};
+use serde::Deserialize;
+use service::AppState;
+use tracing::info;
+
+pub async fn get_maintenance(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(state.maintenance.status())))
+}
+
+pub async fn set_maintenance(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(body): Json<MaintenanceStatus>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        admin = %admin.username,
+        admin_id = %admin.user_id,
+        mode = ?body.mode,
+        message = ?body.message,
+        "maintenance mode changed"
+    );
+    state.maintenance.set(body);
+    Ok((StatusCode::OK, Json(state.maintenance.status())))
+}
+
+#[derive(Deserialize)]
+pub struct SetLogFilterReq {
+    /// `EnvFilter` 格式的过滤规则，例如 `info,sqlx=debug`
+    directive: String,
+    /// 临时生效的秒数，到期后自动恢复
+    ttl_secs: Option<u64>,
+}
+
+pub async fn get_log_filter(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(state.log_filter.status())))
+}
+
+pub async fn set_log_filter(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Json(body): Json<SetLogFilterReq>,
+) -> Result<impl IntoResponse, AppError> {
+    // 先记录再替换，新的过滤规则可能会屏蔽这条日志
+    info!(
+        admin = %admin.username,
+        admin_id = %admin.user_id,
+        from = %state.log_filter.current(),
+        to = %body.directive,
+        ttl_secs = ?body.ttl_secs,
+        "changing log filter"
+    );
+    state.log_filter.set(&body.directive, body.ttl_secs.map(Duration::from_secs))?;
+    Ok((StatusCode::OK, Json(state.log_filter.status())))
+}
+
+pub async fn list_features(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::OK, Json(state.features.list().await?)))
+}
+
+pub async fn set_feature(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(name): Path<String>,
+    Json(rule): Json<FlagRule>,
+) -> Result<impl IntoResponse, AppError> {
+    let status = state.features.set(&name, &rule, &admin.username).await?;
+    info!(
+        admin = %admin.username,
+        admin_id = %admin.user_id,
+        flag = %name,
+        rule = ?rule,
+        "feature flag overridden"
+    );
+    Ok((StatusCode::OK, Json(status)))
+}
+
+pub async fn reset_feature(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let status = state.features.reset(&name).await?;
+    info!(
+        admin = %admin.username,
+        admin_id = %admin.user_id,
+        flag = %name,
+        "feature flag override removed"
+    );
+    Ok((StatusCode::OK, Json(status)))
+}
@@ -0,0 +1,77 @@
+use axum::Router;
+use configure::{
+    error::AppError, log_filter::LogFilterHandle, maintenance::Maintenance, reload::LiveConfig,
+    AppConfig,
+};
+use repositroy::PgPool;
+use service::{feature_flags::FeatureFlags, health::Readiness, AppState, Services};
+
+use crate::route;
+
+/// 一个完整的应用实例，配置、连接池和路由都属于该实例，同一进程中可以同时存在多个
+pub struct App {
+    state: AppState,
+    router: Router,
+}
+
+impl App {
+    pub fn builder(config: AppConfig) -> AppBuilder {
+        AppBuilder { config, pool: None, log_filter: None }
+    }
+
+    /// 当前生效的配置，重新加载后会变化
+    pub fn config(&self) -> &LiveConfig {
+        &self.state.config
+    }
+
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// 挂载了所有中间件的路由
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+}
+
+pub struct AppBuilder {
+    config: AppConfig,
+    pool: Option<PgPool>,
+    log_filter: Option<LogFilterHandle>,
+}
+
+impl AppBuilder {
+    /// 使用已有的连接池，不设置时按 `[database]` 配置创建
+    pub fn pool(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 运行时调整日志级别的句柄，不设置时使用不挂载到 subscriber 的过滤器
+    pub fn log_filter(mut self, log_filter: LogFilterHandle) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    pub async fn build(self) -> Result<App, AppError> {
+        let config = self.config;
+        let pool = match self.pool {
+            Some(pool) => pool,
+            None => repositroy::connect(&config.database).await?,
+        };
+        let log_filter = self
+            .log_filter
+            .unwrap_or_else(|| LogFilterHandle::detached(&config.tracing.filter_directive()));
+        let config = LiveConfig::new(config);
+        let state = AppState {
+            features: FeatureFlags::new(pool.clone(), config.clone()),
+            config,
+            services: Services::new(pool),
+            maintenance: Maintenance::default(),
+            log_filter,
+            readiness: Readiness::default(),
+        };
+        let router = route::api_route(state.clone());
+        Ok(App { state, router })
+    }
+}
@@ -0,0 +1,63 @@
+use clap::Subcommand;
+use configure::{
+    error::AppError, schema::config_schema, validate::ConfigReport, AppConfig, LoadOptions,
+};
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Validate the configuration and report every problem found
+    Check,
+    /// Print the merged configuration as JSON
+    Print {
+        /// Replace passwords, secrets and the database url with `***`
+        #[arg(long)]
+        redacted: bool,
+    },
+    /// Show the merged layers and which layer each effective value came from
+    Sources,
+    /// Print the JSON Schema of the configuration files, for editor validation
+    Schema,
+}
+
+impl ConfigCommand {
+    pub fn run(self, options: &LoadOptions) -> Result<(), AppError> {
+        match self {
+            ConfigCommand::Check => {
+                let (_, report) = load_unchecked(options);
+                if report.has_errors() {
+                    eprint!("{report}");
+                    std::process::exit(1);
+                }
+                for problem in report.warnings() {
+                    println!("{problem}");
+                }
+                println!("configuration is valid");
+            }
+            ConfigCommand::Print { redacted } => {
+                let (config, _) = super::load_config(options);
+                let config = if redacted { config.redacted() } else { config };
+                println!("{}", serde_json::to_string_pretty(&config)?);
+            }
+            ConfigCommand::Sources => {
+                let (config, report) = load_unchecked(options);
+                println!("layers: {}", report.sources);
+                let effective = serde_json::to_value(&config)?;
+                for (path, source) in report.sources.explain(&effective) {
+                    println!("{path} = {source}");
+                }
+            }
+            ConfigCommand::Schema => {
+                println!("{}", serde_json::to_string_pretty(&config_schema())?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取配置但不因检查发现的错误退出，无法读取时退出
+fn load_unchecked(options: &LoadOptions) -> (AppConfig, ConfigReport) {
+    AppConfig::load_unchecked(options).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(1);
+    })
+}
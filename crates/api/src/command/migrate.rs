@@ -0,0 +1,51 @@
+use clap::Subcommand;
+use configure::{error::AppError, LoadOptions};
+use repositroy::migrate;
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Apply all pending migrations
+    Up,
+    /// Revert the most recently applied migrations
+    Down {
+        /// Number of migrations to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// Show applied and pending migrations
+    Status,
+}
+
+impl MigrateCommand {
+    pub async fn run(self, options: &LoadOptions) -> Result<(), AppError> {
+        let (config, _) = super::load_config(options);
+        let services = super::connect(&config).await?;
+        let pool = &services.pool;
+        match self {
+            MigrateCommand::Up => {
+                let pending =
+                    migrate::status(pool).await?.iter().filter(|m| m.is_pending()).count();
+                migrate::run(pool).await?;
+                println!("applied {pending} migration(s)");
+            }
+            MigrateCommand::Down { steps } => {
+                let reverted = migrate::undo(pool, steps).await?;
+                for version in &reverted {
+                    println!("reverted {version}");
+                }
+                println!("reverted {} migration(s)", reverted.len());
+            }
+            MigrateCommand::Status => {
+                for migration in migrate::status(pool).await? {
+                    let state = match migration.success {
+                        Some(true) => "applied",
+                        Some(false) => "failed",
+                        None => "pending",
+                    };
+                    println!("{:<16} {:<8} {}", migration.version, state, migration.description);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+mod config;
+mod migrate;
+mod serve;
+mod token;
+mod user;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use configure::{error::AppError, validate::ConfigReport, AppConfig, LoadOptions};
+use service::Services;
+
+/// axum + sqlx example service
+#[derive(Parser)]
+#[command(name = "api", version)]
+pub struct Cli {
+    /// Directory containing default.toml and the profile files, overrides APP_CONFIG_DIR
+    #[arg(long = "config", value_name = "DIR", global = true)]
+    config_dir: Option<PathBuf>,
+    /// Treat unknown configuration keys as errors instead of warnings, same as APP_CONFIG_STRICT=1
+    #[arg(long, global = true)]
+    strict: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the HTTP server (default)
+    Serve,
+    /// Manage database migrations
+    #[command(subcommand)]
+    Migrate(migrate::MigrateCommand),
+    /// Check, print or describe the configuration
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+    /// Issue access tokens
+    #[command(subcommand)]
+    Token(token::TokenCommand),
+    /// Manage users
+    #[command(subcommand)]
+    User(user::UserCommand),
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<(), AppError> {
+        let options = LoadOptions { config_dir: self.config_dir, strict: self.strict };
+        match self.command.unwrap_or(Command::Serve) {
+            Command::Serve => {
+                let (config, report) = load_config(&options);
+                serve::run(config, report, options).await
+            }
+            Command::Migrate(command) => command.run(&options).await,
+            // config check 需要在配置有错误时也能输出报告，自行读取配置
+            Command::Config(command) => command.run(&options),
+            Command::Token(command) => command.run(&options).await,
+            Command::User(command) => command.run(&options).await,
+        }
+    }
+}
+
+/// 读取并检查配置，有错误时输出所有问题并退出
+fn load_config(options: &LoadOptions) -> (AppConfig, ConfigReport) {
+    match AppConfig::load(options) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 所有子命令共用的数据库连接和服务
+async fn connect(config: &AppConfig) -> Result<Services, AppError> {
+    Ok(Services::new(repositroy::connect(&config.database).await?))
+}
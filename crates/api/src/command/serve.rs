@@ -0,0 +1,89 @@
+use std::{net::SocketAddr, time::Duration};
+
+use api::{route, App};
+use configure::{
+    error::AppError,
+    log_tracing, metrics_recorder,
+    reload::{ConfigReloader, LiveConfig},
+    validate::ConfigReport,
+    AppConfig, LoadOptions,
+};
+use repositroy::spawn_pool_metrics;
+use service::health::Readiness;
+use tokio::signal;
+use tracing::{info, warn};
+
+pub async fn run(
+    app_config: AppConfig,
+    report: ConfigReport,
+    options: LoadOptions,
+) -> Result<(), AppError> {
+    // Initialize tracing, the guard flushes logs and exported spans on shutdown
+    let tracing_guard = log_tracing::init(&app_config);
+    middleware::panic::install_panic_hook();
+    let _metrics_guard = metrics_recorder::init(&app_config);
+    for problem in report.warnings() {
+        warn!("config {}", problem);
+    }
+
+    // Database pool, services and routes
+    let app =
+        App::builder(app_config.clone()).log_filter(tracing_guard.filter_handle()).build().await?;
+    if app_config.metrics.enabled {
+        let interval = Duration::from_secs(app_config.metrics.pool_sample_interval_secs);
+        spawn_pool_metrics(app.state().services.pool.clone(), interval);
+    }
+    let readiness = app.state().readiness.clone();
+    let live = app.config().clone();
+    // SIGHUP or a change in the config dir reloads the reloadable sections
+    ConfigReloader::new(live.clone(), tracing_guard.filter_handle(), options).spawn();
+
+    let server = app_config.server;
+
+    // Metrics on a separate admin port
+    if let (true, Some(port)) = (app_config.metrics.enabled, app_config.metrics.admin_port) {
+        let admin_addr = server.get_socket_addr_with_port(port)?;
+        let admin_listener = tokio::net::TcpListener::bind(admin_addr).await?;
+        info!("metrics listening {}", admin_addr);
+        tokio::spawn(async move { axum::serve(admin_listener, route::metrics_route()).await });
+    }
+
+    let addr = server.get_socket_addr()?;
+    info!("listening {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app.router().into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal(readiness, live))
+        .await?;
+    Ok(())
+}
+
+/// Signal handler for graceful shutdown
+///
+/// Readiness fails right away, then we keep serving for `server.shutdown_delay_secs` so the load
+/// balancer drains us first
+async fn shutdown_signal(readiness: Readiness, config: LiveConfig) {
+    let ctrl_c = async {
+        signal::ctrl_c().await.expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    readiness.mark_shutting_down();
+    let delay = Duration::from_secs(config.load().server.shutdown_delay_secs);
+    info!("shutdown signal received, draining for {:?}", delay);
+    tokio::time::sleep(delay).await;
+}
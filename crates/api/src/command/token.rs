@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use clap::Subcommand;
+use configure::{
+    error::{AppError, ErrorCode},
+    LoadOptions,
+};
+use middleware::jwt::Claims;
+use uuid::Uuid;
+
+#[derive(Subcommand)]
+pub enum TokenCommand {
+    /// Issue a bearer token for an existing user
+    Issue {
+        /// User id or email
+        #[arg(long)]
+        user: String,
+        /// Tenant to put in the token, used for feature flag targeting
+        #[arg(long)]
+        tenant: Option<String>,
+    },
+}
+
+impl TokenCommand {
+    pub async fn run(self, options: &LoadOptions) -> Result<(), AppError> {
+        let (config, _) = super::load_config(options);
+        let services = super::connect(&config).await?;
+        match self {
+            TokenCommand::Issue { user, tenant } => {
+                let found = match Uuid::from_str(&user) {
+                    Ok(id) => services.get_user(id).await?,
+                    Err(_) => services.get_user_by_email(&user).await?,
+                };
+                let found = found.ok_or_else(|| {
+                    AppError::coded(ErrorCode::UserNotFound, format!("User {user} not found"))
+                })?;
+                let id = found.id.to_string();
+                let mut claims =
+                    Claims::build(&config.jwt, &id, &id, &found.name).with_role(&found.role);
+                if let Some(tenant) = &tenant {
+                    claims = claims.with_tenant(tenant);
+                }
+                let token =
+                    claims.to_token(&config.jwt).map_err(|e| AppError::Internal(e.into()))?;
+                println!("{token}");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Subcommand;
+use configure::{error::AppError, LoadOptions};
+
+#[derive(Subcommand)]
+pub enum UserCommand {
+    /// Create an admin user, or promote the user with this email to admin
+    CreateAdmin {
+        #[arg(long)]
+        email: String,
+        /// Display name, defaults to the part of the email before `@`
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+impl UserCommand {
+    pub async fn run(self, options: &LoadOptions) -> Result<(), AppError> {
+        let (config, _) = super::load_config(options);
+        let services = super::connect(&config).await?;
+        match self {
+            UserCommand::CreateAdmin { email, name } => {
+                let existing = services.get_user_by_email(&email).await?;
+                let name = name.unwrap_or_else(|| email.split('@').next().unwrap_or(&email).into());
+                let user = services.create_admin(&email, &name).await?;
+                let action = if existing.is_some() { "promoted" } else { "created" };
+                println!("{action} admin {} ({})", user.email, user.id);
+            }
+        }
+        Ok(())
+    }
+}
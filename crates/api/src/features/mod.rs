@@ -0,0 +1,53 @@
+use std::convert::Infallible;
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, response::IntoResponse};
+use configure::features::FlagContext;
+use middleware::{ctx::LoginUser, extract::Json};
+use serde::Serialize;
+use service::{feature_flags::FeatureFlags, AppState};
+use uuid::Uuid;
+
+/// 绑定了当前请求用户和租户的功能开关，用于 handler 中判断
+pub struct Features {
+    flags: FeatureFlags,
+    ctx: FlagContext,
+}
+
+impl Features {
+    pub async fn is_enabled(&self, name: &str) -> bool {
+        self.flags.is_enabled(name, &self.ctx).await
+    }
+
+    /// 对当前用户开启的所有开关
+    pub async fn enabled(&self) -> Vec<String> {
+        self.flags.enabled(&self.ctx).await
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Features {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // 未登录的请求只能命中对所有人开启的开关
+        let user = parts.extensions.get::<LoginUser>();
+        let ctx = FlagContext {
+            user_id: user.and_then(|user| Uuid::parse_str(&user.user_id).ok()),
+            tenant: user.and_then(|user| user.tenant.clone()),
+        };
+        Ok(Features { flags: state.features.clone(), ctx })
+    }
+}
+
+#[derive(Serialize)]
+pub struct EnabledFeaturesRes {
+    enabled: Vec<String>,
+}
+
+/// 对当前用户开启的功能开关，客户端据此决定展示哪些功能
+pub async fn enabled_features(features: Features) -> impl IntoResponse {
+    Json(EnabledFeaturesRes { enabled: features.enabled().await })
+}
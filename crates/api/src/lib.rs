@@ -0,0 +1,7 @@
+mod admin;
+pub mod app;
+pub mod features;
+pub mod route;
+mod user;
+
+pub use app::{App, AppBuilder};
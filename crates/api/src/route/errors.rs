@@ -0,0 +1,7 @@
+use axum::Json;
+use configure::error::code::{catalog, ErrorCodeInfo};
+
+/// 所有错误码的目录，供前端和 SDK 生成分支逻辑
+pub async fn error_codes() -> Json<Vec<ErrorCodeInfo>> {
+    Json(catalog())
+}
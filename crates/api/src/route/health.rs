@@ -1,10 +1,9 @@
-use middleware::{ctx::LoginUser, jwt::Claims};
+use std::time::Duration;
 
-pub async fn example_user() -> String {
-    let claims = Claims::build("sub", "1", "test");
-    let token = claims.to_token().unwrap();
-    token
-}
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use configure::error;
+use middleware::ctx::LoginUser;
+use service::AppState;
 
 pub async fn example_user_info(user: LoginUser) -> String {
     format!("Hello, {}! Your user_id is {}. Token exp: {}", user.username, user.user_id, user.exp)
@@ -13,3 +12,17 @@ pub async fn example_user_info(user: LoginUser) -> String {
 pub async fn health() -> String {
     "server is ok".into()
 }
+
+/// 存活检查
+pub async fn live(State(state): State<AppState>) -> impl IntoResponse {
+    (StatusCode::OK, Json(state.services.liveness()))
+}
+
+/// 就绪检查，任一检查失败时返回 503，隐藏内部错误细节时也隐藏失败原因
+pub async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let timeout = Duration::from_millis(state.config.load().server.readiness_timeout_ms);
+    let report = state.services.readiness(&state.readiness, timeout).await;
+    let report = if error::expose_internal_errors() { report } else { report.masked() };
+    let status = if report.is_up() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
@@ -0,0 +1,7 @@
+use axum::{http::header::CONTENT_TYPE, response::IntoResponse};
+use configure::metrics_recorder;
+
+/// Prometheus 文本格式的指标
+pub async fn metrics() -> impl IntoResponse {
+    ([(CONTENT_TYPE, "text/plain; version=0.0.4")], metrics_recorder::render())
+}
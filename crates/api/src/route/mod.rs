@@ -2,28 +2,71 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
-use middleware;
 use service::AppState;
+pub mod errors;
 pub mod health;
+pub mod metrics;
 pub use health as other_health;
 
-use crate::user;
+use crate::{admin, features, user};
 
 pub fn api_route(state: AppState) -> Router {
-    let auth_route_with_middleware = middleware::apply(auth_route(state));
-    Router::new()
-        .merge(none_auth_route())  // first merge none_auth  route
-        .merge(auth_route_with_middleware)       //  merge auth route
+    let live = state.config.clone();
+    let config = live.load_full();
+    let jwt = &config.jwt;
+    let maintenance = state.maintenance.clone();
+    let admin_route_with_middleware = middleware::apply_access(
+        middleware::apply_auth(admin_route(state.clone()), jwt),
+        &live,
+        "admin",
+    );
+    let auth_route_with_middleware = middleware::apply_access(
+        middleware::apply_auth(auth_route(state.clone()), jwt),
+        &live,
+        "api",
+    );
+    let none_auth_route_with_middleware =
+        middleware::apply_access(none_auth_route(state), &live, "public");
+    let mut router = Router::new()
+        .merge(none_auth_route_with_middleware) // first merge none_auth  route
+        .merge(admin_route_with_middleware) // merge admin route
+        .merge(auth_route_with_middleware); //  merge auth route
+
+    // without a dedicated admin port, /metrics is served here only to network.access.admin.allow,
+    // and denied to everyone while that list is empty
+    if config.metrics.admin_port.is_none() {
+        router = router.merge(middleware::apply_allow_list(metrics_route(), &live, "admin"));
+    }
 
+    // request id / client ip / trace / cors apply to every route
+    middleware::apply(router, &live, maintenance)
 }
 
-pub fn none_auth_route() -> Router {
+/// Prometheus scrape endpoint
+pub fn metrics_route() -> Router {
+    Router::new().route("/metrics", get(metrics::metrics))
+}
+
+/// Admin routes, handlers require the `admin` role
+pub fn admin_route(state: AppState) -> Router {
+    Router::new()
+        .route("/admin/maintenance", get(admin::get_maintenance).put(admin::set_maintenance))
+        .route("/admin/log-level", get(admin::get_log_filter).put(admin::set_log_filter))
+        .route("/admin/features", get(admin::list_features))
+        .route("/admin/features/:name", put(admin::set_feature).delete(admin::reset_feature))
+        .with_state(state)
+}
+
+pub fn none_auth_route(state: AppState) -> Router {
     Router::new()
         .route("/health", get(other_health::health))
-        .route("/example/token", get(other_health::example_user))
+        .route("/health/live", get(other_health::live))
+        .route("/health/ready", get(other_health::ready))
+        .route("/error-codes", get(errors::error_codes))
+        .with_state(state)
 }
 
 pub fn auth_route(state: AppState) -> Router {
@@ -31,6 +74,7 @@ pub fn auth_route(state: AppState) -> Router {
         .route("/users", post(user::create_user).get(user::list_users).put(user::update_user))
         .route("/users/:id", get(user::get_user).delete(user::del_user))
         .route("/example/user", get(other_health::example_user_info))
+        .route("/features", get(features::enabled_features))
         .with_state(state)
         .fallback(fallback_handler)
 }
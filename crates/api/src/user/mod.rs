@@ -1,14 +1,11 @@
 use std::str::FromStr;
 
-use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Json,
-};
-use configure::error::AppError;
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use configure::error::{AppError, ErrorCode};
+use middleware::extract::{Json, Path, Query};
 use repositroy::User;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use service::AppState;
 
 #[derive(Deserialize)]
@@ -22,6 +19,7 @@ pub struct UserRes {
     id: String,
     email: String,
     name: String,
+    role: String,
     created_at: String,
 }
 
@@ -31,6 +29,7 @@ impl From<User> for UserRes {
             id: u.id.to_string(),
             email: u.email,
             name: u.name,
+            role: u.role,
             created_at: match u.created_at {
                 Some(t) => t.to_rfc3339(),
                 None => "".to_string(),
@@ -76,11 +75,10 @@ pub async fn get_user(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid =
-        uuid::Uuid::from_str(&id).map_err(|_| AppError::BadRequest("invalid id".to_string()))?;
+    let uid = uuid::Uuid::from_str(&id).map_err(|_| invalid_id(&id))?;
     match state.services.get_user(uid).await? {
         Some(user) => Ok((StatusCode::OK, Json(UserRes::from(user)))),
-        None => Err(AppError::NotFound),
+        None => Err(user_not_found(uid)),
     }
 }
 
@@ -88,10 +86,17 @@ pub async fn del_user(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
-    let uid =
-        uuid::Uuid::from_str(&id).map_err(|_| AppError::BadRequest("invalid id".to_string()))?;
+    let uid = uuid::Uuid::from_str(&id).map_err(|_| invalid_id(&id))?;
     match state.services.del_user(uid).await? {
         Some(user) => Ok((StatusCode::OK, Json(UserRes::from(user)))),
-        None => Err(AppError::NotFound),
+        None => Err(user_not_found(uid)),
     }
 }
+
+fn invalid_id(id: &str) -> AppError {
+    AppError::coded(ErrorCode::InvalidId, "invalid id").with_details(json!({ "id": id }))
+}
+
+fn user_not_found(id: uuid::Uuid) -> AppError {
+    AppError::coded(ErrorCode::UserNotFound, "user not found").with_details(json!({ "id": id }))
+}
@@ -0,0 +1,49 @@
+//! 同一进程中的多个应用实例各自使用自己的配置
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::StatusCode,
+};
+use configure::{profile::Profile, AppConfig};
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// 数据库不可达的配置，就绪检查一定失败
+fn unreachable_db(profile: &str) -> AppConfig {
+    let mut config = common::config();
+    config.profile = Profile::new(profile).unwrap();
+    config.database.url = None;
+    config.database.port = 1;
+    config.database.acquire_timeout_secs = 1;
+    config.server.readiness_timeout_ms = 2000;
+    config
+}
+
+async fn ready(app: &api::App) -> (StatusCode, Value) {
+    let request = common::request("GET", "/health/ready", Body::empty());
+    let response = app.router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn apps_keep_their_own_error_detail_setting() {
+    let development = common::app(unreachable_db(Profile::DEVELOPMENT)).await;
+    let production = common::app(unreachable_db(Profile::PRODUCTION)).await;
+
+    let (status, body) = ready(&production).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["checks"]["database"]["detail"], "check failed, see server logs");
+
+    let (status, body) = ready(&development).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let detail = body["checks"]["database"]["detail"].as_str().unwrap();
+    assert_ne!(detail, "check failed, see server logs");
+
+    // 先构建的实例不受后构建的实例影响
+    let (_, body) = ready(&production).await;
+    assert_eq!(body["checks"]["database"]["detail"], "check failed, see server logs");
+}
@@ -0,0 +1,32 @@
+//! 集成测试共用的配置和应用实例
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+
+use api::App;
+use axum::{body::Body, extract::ConnectInfo, http::Request};
+use configure::AppConfig;
+
+/// 项目 `setting/` 目录中当前 profile(默认 development)的配置
+pub fn config() -> AppConfig {
+    AppConfig::read().expect("failed to read config")
+}
+
+/// 第一次查询时才连接数据库的应用实例，不访问数据库的路由不需要数据库
+pub async fn app(config: AppConfig) -> App {
+    let pool = repositroy::connect_lazy(&config.database).expect("invalid database config");
+    App::builder(config).pool(pool).build().await.expect("failed to build app")
+}
+
+/// 来自本机的请求，与 `into_make_service_with_connect_info` 启动的服务一样带有直连地址
+pub fn request(method: &str, uri: &str, body: Body) -> Request<Body> {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .unwrap();
+    let peer: SocketAddr = "127.0.0.1:40000".parse().unwrap();
+    request.extensions_mut().insert(ConnectInfo(peer));
+    request
+}
@@ -0,0 +1,61 @@
+//! 中间件返回的错误响应同样带有 CORS 头，浏览器才能读取 JSON 错误和 `Retry-After`
+
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, Response, StatusCode},
+};
+use configure::{
+    maintenance::{MaintenanceMode, MaintenanceStatus},
+    AppConfig,
+};
+use tower::ServiceExt;
+
+const ORIGIN: &str = "https://app.example.com";
+
+async fn send(
+    config: AppConfig,
+    mode: MaintenanceMode,
+    mut request: Request<Body>,
+) -> Response<Body> {
+    let app = common::app(config).await;
+    app.state().maintenance.set(MaintenanceStatus { mode, message: None });
+    request.headers_mut().insert(header::ORIGIN, ORIGIN.parse().unwrap());
+    app.router().oneshot(request).await.unwrap()
+}
+
+fn allow_origin(response: &Response<Body>) -> Option<&str> {
+    response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).map(|v| v.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn shed_responses_carry_cors_headers() {
+    let mut config = common::config();
+    config.server.max_in_flight = Some(0);
+    let request = common::request("GET", "/error-codes", Body::empty());
+    let response = send(config, MaintenanceMode::Off, request).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(response.headers().contains_key(header::RETRY_AFTER));
+    assert!(allow_origin(&response).is_some());
+}
+
+#[tokio::test]
+async fn maintenance_responses_carry_cors_headers() {
+    let request = common::request("GET", "/error-codes", Body::empty());
+    let response = send(common::config(), MaintenanceMode::Maintenance, request).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(allow_origin(&response).is_some());
+}
+
+#[tokio::test]
+async fn preflight_is_answered_during_maintenance() {
+    let mut request = common::request("OPTIONS", "/users", Body::empty());
+    request.headers_mut().insert(header::ACCESS_CONTROL_REQUEST_METHOD, "POST".parse().unwrap());
+    let response = send(common::config(), MaintenanceMode::Maintenance, request).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(allow_origin(&response).is_some());
+}
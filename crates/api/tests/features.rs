@@ -0,0 +1,50 @@
+//! `Features` 提取器按请求的用户和租户判断功能开关
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::AUTHORIZATION, StatusCode},
+};
+use configure::features::FlagConfig;
+use middleware::jwt::Claims;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn enabled_features(tenant: Option<&str>) -> Value {
+    let mut config = common::config();
+    // 数据库不可达时没有运行时覆盖，使用配置中的默认规则
+    config.database.url = None;
+    config.database.port = 1;
+    config.database.acquire_timeout_secs = 1;
+    let flags = &mut config.features.flags;
+    flags.insert("everyone".to_string(), FlagConfig { enabled: true, ..Default::default() });
+    flags.insert(
+        "acme_only".to_string(),
+        FlagConfig { enabled: true, tenants: vec!["acme".to_string()], ..Default::default() },
+    );
+    flags.insert("off".to_string(), FlagConfig::default());
+
+    let mut claims = Claims::build(&config.jwt, "test", &uuid::Uuid::nil().to_string(), "test");
+    if let Some(tenant) = tenant {
+        claims = claims.with_tenant(tenant);
+    }
+    let token = claims.to_token(&config.jwt).unwrap();
+    let mut request = common::request("GET", "/features", Body::empty());
+    request.headers_mut().insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+
+    let response = common::app(config).await.router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn lists_flags_enabled_for_the_caller() {
+    assert_eq!(
+        enabled_features(Some("acme")).await,
+        json!({ "enabled": ["acme_only", "everyone"] })
+    );
+    assert_eq!(enabled_features(Some("other")).await, json!({ "enabled": ["everyone"] }));
+    assert_eq!(enabled_features(None).await, json!({ "enabled": ["everyone"] }));
+}
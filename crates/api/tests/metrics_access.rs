@@ -0,0 +1,35 @@
+//! 没有单独管理端口时业务端口上 `/metrics` 的访问控制
+
+mod common;
+
+use axum::{body::Body, http::StatusCode};
+use configure::network::IpAccessConfig;
+use tower::ServiceExt;
+
+async fn metrics_status(access: Option<IpAccessConfig>) -> StatusCode {
+    let mut config = common::config();
+    config.metrics.admin_port = None;
+    config.network.access.remove("admin");
+    if let Some(access) = access {
+        config.network.access.insert("admin".to_string(), access);
+    }
+    let app = common::app(config).await;
+    let request = common::request("GET", "/metrics", Body::empty());
+    app.router().oneshot(request).await.unwrap().status()
+}
+
+fn allow(net: &str) -> IpAccessConfig {
+    IpAccessConfig { allow: vec![net.parse().unwrap()], deny: Vec::new() }
+}
+
+#[tokio::test]
+async fn metrics_denied_without_admin_allow_list() {
+    assert_eq!(metrics_status(None).await, StatusCode::FORBIDDEN);
+    assert_eq!(metrics_status(Some(IpAccessConfig::default())).await, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn metrics_follow_admin_allow_list() {
+    assert_eq!(metrics_status(Some(allow("127.0.0.0/8"))).await, StatusCode::OK);
+    assert_eq!(metrics_status(Some(allow("10.0.0.0/8"))).await, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,77 @@
+//! 使用进程内的 OTLP collector 验证请求 span 会被导出
+
+mod common;
+
+use std::time::Duration;
+
+use axum::body::Body;
+use configure::log_tracing;
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    trace_service_server::{TraceService, TraceServiceServer},
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+use tower::ServiceExt;
+
+/// 把收到的 span 名称转发到 channel 的 collector
+struct Collector(mpsc::UnboundedSender<String>);
+
+#[tonic::async_trait]
+impl TraceService for Collector {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        let spans = request
+            .into_inner()
+            .resource_spans
+            .into_iter()
+            .flat_map(|resource| resource.scope_spans)
+            .flat_map(|scope| scope.spans);
+        for span in spans {
+            let _ = self.0.send(span.name);
+        }
+        Ok(Response::new(ExportTraceServiceResponse { partial_success: None }))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exports_request_span_to_collector() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(
+        Server::builder()
+            .add_service(TraceServiceServer::new(Collector(tx)))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let mut config = common::config();
+    config.otlp.enabled = true;
+    config.otlp.endpoint = format!("http://{addr}");
+    config.otlp.sampling_ratio = 1.0;
+    config.tracing.console.enabled = false;
+    config.tracing.file.enabled = false;
+    let guard = log_tracing::init(&config);
+
+    let app = common::app(config).await;
+    let response =
+        app.router().oneshot(common::request("GET", "/health/live", Body::empty())).await.unwrap();
+    assert!(response.status().is_success());
+    // 响应体被 drop 后请求 span 才结束，关闭 tracer provider 时导出剩余的 span
+    drop(response);
+    tokio::task::spawn_blocking(move || drop(guard)).await.unwrap();
+
+    let received = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(name) = rx.recv().await {
+            if name == "request" {
+                return true;
+            }
+        }
+        false
+    })
+    .await;
+    assert_eq!(received, Ok(true), "collector did not receive the request span");
+}
@@ -0,0 +1,42 @@
+//! handler panic 时返回标准的 JSON 500 并计数
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use configure::{maintenance::Maintenance, metrics_recorder, reload::LiveConfig};
+use serde_json::Value;
+use tower::ServiceExt;
+
+async fn boom() -> &'static str {
+    panic!("boom")
+}
+
+#[tokio::test]
+async fn panicking_handler_returns_json_500_and_is_counted() {
+    let mut config = common::config();
+    config.metrics.enabled = true;
+    config.metrics.otlp_export = false;
+    let _metrics = metrics_recorder::init(&config);
+    middleware::panic::install_panic_hook();
+
+    let live = LiveConfig::new(config);
+    let router =
+        middleware::apply(Router::new().route("/boom", get(boom)), &live, Maintenance::default());
+    let response = router.oneshot(common::request("GET", "/boom", Body::empty())).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error_code"], "INTERNAL_ERROR");
+    assert!(body["request_id"].is_string());
+    // 开发环境返回细节，但不会包含 panic 信息
+    assert!(!body["message"].as_str().unwrap().contains("boom"));
+
+    let rendered = metrics_recorder::render();
+    assert!(rendered.lines().any(|line| line == "http_panics_total 1"), "{rendered}");
+}
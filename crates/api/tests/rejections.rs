@@ -0,0 +1,59 @@
+//! 提取器拒绝请求时返回带错误码的统一错误响应
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::AUTHORIZATION, Request, StatusCode},
+};
+use middleware::jwt::Claims;
+use serde_json::Value;
+use tower::ServiceExt;
+
+async fn send(mut request: Request<Body>, role: Option<&str>) -> (StatusCode, Value) {
+    let config = common::config();
+    let mut claims = Claims::build(&config.jwt, "test", "1", "test");
+    if let Some(role) = role {
+        claims = claims.with_role(role);
+    }
+    let token = claims.to_token(&config.jwt).unwrap();
+    request.headers_mut().insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+
+    let response = common::app(config).await.router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn malformed_json_body_is_coded() {
+    let request = common::request("POST", "/users", Body::from("{\"email\":"));
+    let (status, body) = send(request, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error_code"], "INVALID_BODY");
+}
+
+#[tokio::test]
+async fn json_body_without_content_type_is_coded() {
+    let mut request = common::request("POST", "/users", Body::from("{}"));
+    request.headers_mut().remove("content-type");
+    let (status, body) = send(request, None).await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(body["error_code"], "UNSUPPORTED_MEDIA_TYPE");
+}
+
+#[tokio::test]
+async fn invalid_query_is_coded() {
+    let request = common::request("GET", "/users?limit=many", Body::empty());
+    let (status, body) = send(request, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error_code"], "INVALID_QUERY");
+}
+
+#[tokio::test]
+async fn admin_routes_reject_other_roles_with_code() {
+    let request = common::request("GET", "/admin/maintenance", Body::empty());
+    let (status, body) = send(request, Some("user")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error_code"], "ADMIN_REQUIRED");
+}
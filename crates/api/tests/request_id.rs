@@ -0,0 +1,43 @@
+//! 错误响应带上请求的 request id，便于和服务端日志对应
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::AUTHORIZATION, StatusCode},
+};
+use middleware::{jwt::Claims, request_id::X_REQUEST_ID};
+use serde_json::Value;
+use tower::ServiceExt;
+
+async fn error_body(request_id: Option<&str>) -> (Option<String>, Value) {
+    let config = common::config();
+    let token = Claims::build(&config.jwt, "test", "1", "test").to_token(&config.jwt).unwrap();
+    let mut request = common::request("GET", "/users/not-a-uuid", Body::empty());
+    request.headers_mut().insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+    if let Some(request_id) = request_id {
+        request.headers_mut().insert(X_REQUEST_ID, request_id.parse().unwrap());
+    }
+
+    let response = common::app(config).await.router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let header = response.headers().get(X_REQUEST_ID).map(|v| v.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (header, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn error_body_carries_the_incoming_request_id() {
+    let (header, body) = error_body(Some("req-123")).await;
+    assert_eq!(body["error_code"], "INVALID_ID");
+    assert_eq!(body["request_id"], "req-123");
+    assert_eq!(header.as_deref(), Some("req-123"));
+}
+
+#[tokio::test]
+async fn error_body_carries_the_generated_request_id() {
+    let (header, body) = error_body(None).await;
+    let header = header.expect("response has no x-request-id");
+    assert!(!header.is_empty());
+    assert_eq!(body["request_id"], header.as_str());
+}
@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+
+use config::{ConfigError, File, FileFormat, Source};
+
+use crate::profile::Profile;
+
+/// 指定配置目录的环境变量
+pub const CONFIG_DIR_ENV: &str = "APP_CONFIG_DIR";
+
+/// 编译进二进制的配置文件，找不到配置目录时使用
+const EMBEDDED: [(&str, &str); 5] = [
+    ("default", include_str!("../../../setting/default.toml")),
+    ("development", include_str!("../../../setting/development.toml")),
+    ("production", include_str!("../../../setting/production.toml")),
+    ("staging", include_str!("../../../setting/staging.toml")),
+    ("test", include_str!("../../../setting/test.toml")),
+];
+
+/// 配置源及其描述，描述用于报告配置项的来源
+pub type NamedSource = (String, Box<dyn Source + Send + Sync>);
+
+/// 配置文件的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigDir {
+    Dir(PathBuf),
+    Embedded,
+}
+
+impl ConfigDir {
+    /// 依次查找 `explicit`(命令行 `--config`)、`APP_CONFIG_DIR`、项目根目录和当前目录下的 `setting/`，
+    /// 都没有则使用内置配置
+    ///
+    /// 显式指定的目录不存在时返回错误
+    pub fn discover(explicit: Option<&Path>) -> Result<ConfigDir, ConfigError> {
+        let explicit = explicit
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from));
+        if let Some(dir) = explicit {
+            if !dir.is_dir() {
+                return Err(ConfigError::Message(format!(
+                    "config dir {} does not exist",
+                    dir.display()
+                )));
+            }
+            return Ok(ConfigDir::Dir(dir));
+        }
+        let found = get_root_dir()
+            .map(|root| root.join("setting"))
+            .into_iter()
+            .chain(std::env::current_dir().map(|dir| dir.join("setting")))
+            .find(|dir| dir.is_dir());
+        Ok(found.map_or(ConfigDir::Embedded, ConfigDir::Dir))
+    }
+
+    /// 未设置 `ENVIRONMENT` 时使用的 profile
+    ///
+    /// 配置目录中为 development；内置配置用于发布的二进制，不回退到开发环境(弱密钥、返回错误细节)
+    pub fn default_profile(&self) -> Result<Profile, ConfigError> {
+        match self {
+            ConfigDir::Dir(_) => Ok(Profile::default()),
+            ConfigDir::Embedded => Err(ConfigError::Message(
+                "ENVIRONMENT must be set when no config dir is found and the built-in config is used"
+                    .into(),
+            )),
+        }
+    }
+
+    /// `{name}.toml` 配置源及其描述，可选的文件不存在时返回 `None`
+    pub fn file(&self, name: &str, required: bool) -> Result<Option<NamedSource>, ConfigError> {
+        match self {
+            ConfigDir::Dir(dir) => {
+                let path = dir.join(format!("{name}.toml"));
+                if !path.is_file() && required {
+                    let message = format!("config file {} does not exist", path.display());
+                    return Err(ConfigError::Message(message));
+                }
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                Ok(Some((path.display().to_string(), Box::new(File::from(path)))))
+            }
+            ConfigDir::Embedded => match EMBEDDED.iter().find(|(embedded, _)| *embedded == name) {
+                Some((_, content)) => Ok(Some((
+                    format!("built-in {name}.toml"),
+                    Box::new(File::from_str(content, FileFormat::Toml)),
+                ))),
+                None if required => {
+                    Err(ConfigError::Message(format!("no built-in config for profile `{name}`")))
+                }
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// 沿配置文件中的 `extends` 找出 profile 继承的所有 profile
+    pub fn resolve_profile(&self, profile: Profile) -> Result<Profile, ConfigError> {
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut current = profile.name().to_string();
+        while let Some(parent) = self.extends_of(&current)? {
+            let parent = Profile::new(&parent)?.name().to_string();
+            if parent == profile.name() || ancestors.contains(&parent) {
+                let chain: Vec<&str> = std::iter::once(profile.name())
+                    .chain(ancestors.iter().map(String::as_str))
+                    .chain([parent.as_str()])
+                    .collect();
+                return Err(ConfigError::Message(format!(
+                    "profile `{}` has an `extends` cycle: {}",
+                    profile.name(),
+                    chain.join(" -> ")
+                )));
+            }
+            ancestors.push(parent.clone());
+            current = parent;
+        }
+        Ok(profile.with_ancestors(ancestors))
+    }
+
+    fn extends_of(&self, name: &str) -> Result<Option<String>, ConfigError> {
+        let Some((_, source)) = self.file(name, true)? else {
+            return Ok(None);
+        };
+        source.collect()?.remove("extends").map(|value| value.into_string()).transpose()
+    }
+}
+
+/// 项目根目录，即包含 `Cargo.lock` 的目录
+pub fn get_root_dir() -> Result<PathBuf, ConfigError> {
+    let mut current = std::env::current_dir().map_err(|e| ConfigError::Message(e.to_string()))?;
+    while current.parent().is_some() {
+        if current.join("Cargo.lock").exists() {
+            return Ok(current);
+        }
+        current = current.parent().unwrap().to_path_buf();
+    }
+    Err(ConfigError::Message("Cannot find Cargo.lock in any parent directory".into()))
+}
+
+/// 相对路径的基准目录，不在 Cargo workspace 中运行时为当前目录
+pub fn base_dir() -> PathBuf {
+    get_root_dir()
+        .ok()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 临时配置目录，`files` 为 (profile, extends)
+    fn config_dir(test: &str, files: &[(&str, Option<&str>)]) -> ConfigDir {
+        let dir = std::env::temp_dir().join(format!("config-dir-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, extends) in files {
+            let content =
+                extends.map(|parent| format!("extends = \"{parent}\"\n")).unwrap_or_default();
+            std::fs::write(dir.join(format!("{name}.toml")), content).unwrap();
+        }
+        ConfigDir::Dir(dir)
+    }
+
+    #[test]
+    fn discovers_explicit_or_workspace_dir() {
+        let dir = std::env::temp_dir().join(format!("config-dir-{}-explicit", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(ConfigDir::discover(Some(&dir)).unwrap(), ConfigDir::Dir(dir));
+
+        let error = ConfigDir::discover(Some(Path::new("/nonexistent/setting"))).unwrap_err();
+        assert!(error.to_string().contains("/nonexistent/setting does not exist"), "{error}");
+
+        // 测试在 workspace 中运行，找到根目录下的 setting/
+        let root = get_root_dir().unwrap();
+        assert_eq!(ConfigDir::discover(None).unwrap(), ConfigDir::Dir(root.join("setting")));
+    }
+
+    #[test]
+    fn embedded_config_requires_an_explicit_profile() {
+        let error = ConfigDir::Embedded.default_profile().unwrap_err().to_string();
+        assert!(error.contains("ENVIRONMENT must be set"), "{error}");
+        assert!(ConfigDir::Dir(PathBuf::from("setting"))
+            .default_profile()
+            .unwrap()
+            .is_development());
+
+        let (name, _) = ConfigDir::Embedded.file("production", true).unwrap().unwrap();
+        assert_eq!(name, "built-in production.toml");
+        assert!(ConfigDir::Embedded.file("local", false).unwrap().is_none());
+        assert!(ConfigDir::Embedded.file("qa", true).is_err());
+
+        let profile =
+            ConfigDir::Embedded.resolve_profile(Profile::new("staging").unwrap()).unwrap();
+        assert!(profile.is_production());
+    }
+
+    fn resolve(dir: &ConfigDir, name: &str) -> Result<Profile, ConfigError> {
+        dir.resolve_profile(Profile::new(name).unwrap())
+    }
+
+    #[test]
+    fn resolves_ancestors_nearest_first() {
+        let dir = config_dir(
+            "chain",
+            &[("production", None), ("staging", Some("production")), ("qa", Some("staging"))],
+        );
+        let profile = resolve(&dir, "qa").unwrap();
+        assert_eq!(profile.ancestors(), ["staging", "production"]);
+        assert!(profile.is_production());
+    }
+
+    #[test]
+    fn rejects_extends_cycles() {
+        let dir = config_dir("cycle", &[("a", Some("b")), ("b", Some("c")), ("c", Some("a"))]);
+        let error = resolve(&dir, "a").unwrap_err().to_string();
+        assert!(error.contains("a -> b -> c -> a"), "{error}");
+
+        // 不经过起点的环
+        let dir =
+            config_dir("inner-cycle", &[("a", Some("b")), ("b", Some("c")), ("c", Some("b"))]);
+        let error = resolve(&dir, "a").unwrap_err().to_string();
+        assert!(error.contains("a -> b -> c -> b"), "{error}");
+
+        let dir = config_dir("self", &[("a", Some("a"))]);
+        assert!(resolve(&dir, "a").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_parents() {
+        let dir = config_dir("missing", &[("a", Some("gone"))]);
+        assert!(resolve(&dir, "a").unwrap_err().to_string().contains("does not exist"));
+
+        let dir = config_dir("invalid", &[("a", Some("Prod"))]);
+        assert!(resolve(&dir, "a").unwrap_err().to_string().contains("invalid profile"));
+    }
+}
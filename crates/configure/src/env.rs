@@ -1,14 +1,112 @@
-use std::str::FromStr;
+use config::{ConfigError, Map};
 
-use super::profile::Profile;
+use super::{profile::Profile, secret::read_secret_file};
+
+/// 环境变量中嵌套配置项的分隔符，例如 `APP__SERVER__MAX_IN_FLIGHT` 对应 `server.max_in_flight`
+const ENV_SEPARATOR: &str = "__";
+
+/// 以该后缀结尾的环境变量表示从文件读取配置值，例如 `APP__JWT__SECRET_FILE`
+const FILE_ENV_SUFFIX: &str = "_FILE";
+
+/// `{prefix}__*` 环境变量中的一个配置项
+pub struct EnvOverride {
+    /// 环境变量名
+    pub var: String,
+    /// 配置项路径，例如 `server.max_in_flight`
+    pub key: String,
+    pub value: String,
+}
 
 pub fn get_env_source(prefix: &str) -> config::Environment {
-    config::Environment::with_prefix(prefix).try_parsing(true).prefix_separator("__").separator("_")
+    // `*_FILE` 变量由 `get_file_env_overrides` 单独处理
+    let vars: Map<String, String> =
+        std::env::vars().filter(|(name, _)| !name.ends_with(FILE_ENV_SUFFIX)).collect();
+    config::Environment::with_prefix(prefix)
+        .try_parsing(true)
+        .prefix_separator(ENV_SEPARATOR)
+        .separator(ENV_SEPARATOR)
+        .source(Some(vars))
+}
+
+/// `{prefix}__*` 环境变量(不含 `*_FILE`)对应的配置项，与 [`get_env_source`] 的解析规则一致
+pub fn get_env_overrides(prefix: &str) -> Vec<EnvOverride> {
+    std::env::vars()
+        .filter(|(var, _)| !var.ends_with(FILE_ENV_SUFFIX))
+        .filter_map(|(var, value)| Some(EnvOverride { key: env_key(prefix, &var)?, var, value }))
+        .collect()
+}
+
+/// 读取 `{prefix}__*_FILE` 环境变量指向的文件，`value` 为文件内容
+///
+/// 例如 `APP__JWT__SECRET_FILE=/run/secrets/jwt` 会把文件内容作为 `jwt.secret`
+pub fn get_file_env_overrides(prefix: &str) -> Result<Vec<EnvOverride>, ConfigError> {
+    std::env::vars()
+        .filter_map(|(var, path)| {
+            let key = env_key(prefix, var.strip_suffix(FILE_ENV_SUFFIX)?)?;
+            Some((var, key, path))
+        })
+        .map(|(var, key, path)| {
+            read_secret_file(&path, &key).map(|value| EnvOverride { var, key, value })
+        })
+        .collect()
+}
+
+/// 环境变量名对应的配置项路径，不以 `{prefix}__` 开头时为 `None`
+fn env_key(prefix: &str, var: &str) -> Option<String> {
+    let var = var.to_lowercase();
+    let key = var.strip_prefix(&format!("{prefix}{ENV_SEPARATOR}").to_lowercase())?;
+    (!key.is_empty()).then(|| key.replace(ENV_SEPARATOR, "."))
 }
 
-pub fn get_profile() -> Result<Profile, config::ConfigError> {
+/// 由 `ENVIRONMENT` 环境变量选择 profile，未设置时返回 `None`，由配置目录决定默认 profile
+pub fn get_profile() -> Result<Option<Profile>, config::ConfigError> {
     dotenvy::dotenv().ok();
-    std::env::var("ENVIRONMENT")
-        .map(|env| Profile::from_str(&env).map_err(|e| config::ConfigError::Message(e.to_string())))
-        .unwrap_or_else(|_e| Ok(Profile::Development))
+    std::env::var("ENVIRONMENT").ok().map(|env| Profile::new(&env)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("env-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn maps_var_names_to_keys() {
+        assert_eq!(env_key("APP", "APP__SERVER__MAX_IN_FLIGHT").unwrap(), "server.max_in_flight");
+        assert_eq!(env_key("APP", "app__jwt__secret").unwrap(), "jwt.secret");
+        assert_eq!(env_key("APP", "APP__"), None);
+        assert_eq!(env_key("APP", "APP_CONFIG_STRICT"), None);
+        assert_eq!(env_key("APP", "OTHER__SERVER__PORT"), None);
+    }
+
+    #[test]
+    fn file_overrides_read_the_file() {
+        let path = temp_file("jwt", "from-file\n");
+        // 每个测试使用单独的前缀，避免影响并行执行的其他测试
+        std::env::set_var("ENVTEST_FILE__JWT__SECRET_FILE", &path);
+        std::env::set_var("ENVTEST_FILE__SERVER__PORT", "8080");
+
+        let overrides = get_file_env_overrides("ENVTEST_FILE").unwrap();
+        assert_eq!(overrides.len(), 1);
+        assert_eq!(overrides[0].var, "ENVTEST_FILE__JWT__SECRET_FILE");
+        assert_eq!(overrides[0].key, "jwt.secret");
+        assert_eq!(overrides[0].value, "from-file");
+
+        // `*_FILE` 变量不作为普通配置项
+        let keys: Vec<_> =
+            get_env_overrides("ENVTEST_FILE").into_iter().map(|env| env.key).collect();
+        assert_eq!(keys, ["server.port"]);
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        std::env::set_var("ENVTEST_MISSING__DATABASE__PASSWORD_FILE", "/nonexistent/db_password");
+        let error = get_file_env_overrides("ENVTEST_MISSING").err().unwrap().to_string();
+        assert!(error.contains("/nonexistent/db_password"), "{error}");
+        assert!(error.contains("database.password"), "{error}");
+    }
 }
@@ -0,0 +1,146 @@
+use axum::http::StatusCode;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+/// 稳定的错误码，客户端应根据它而不是 `message` 做分支判断
+///
+/// 已发布的错误码只能新增，不能修改或删除
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::EnumIter, strum::AsRefStr, strum::Display,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // 通用错误
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    ServiceUnavailable,
+    InternalError,
+    DatabaseError,
+    DatabaseBusy,
+    ConfigError,
+    IoError,
+    SerializationError,
+    AddrParseError,
+
+    // 请求参数
+    InvalidId,
+    InvalidLogFilter,
+    InvalidFeatureFlag,
+    InvalidBody,
+    InvalidPath,
+    InvalidQuery,
+    UnsupportedMediaType,
+    PayloadTooLarge,
+
+    // 认证与授权
+    AuthTokenMissing,
+    AuthTokenInvalid,
+    AuthTokenExpired,
+    AdminRequired,
+    IpDenied,
+
+    // 服务状态
+    MaintenanceMode,
+    ReadOnlyMode,
+    ServerOverloaded,
+
+    // 用户
+    UserNotFound,
+
+    // 功能开关
+    FeatureFlagNotFound,
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest
+            | Self::InvalidId
+            | Self::InvalidLogFilter
+            | Self::InvalidFeatureFlag
+            | Self::InvalidBody
+            | Self::InvalidPath
+            | Self::InvalidQuery => StatusCode::BAD_REQUEST,
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unauthorized
+            | Self::AuthTokenMissing
+            | Self::AuthTokenInvalid
+            | Self::AuthTokenExpired => StatusCode::UNAUTHORIZED,
+            Self::Forbidden | Self::AdminRequired | Self::IpDenied => StatusCode::FORBIDDEN,
+            Self::NotFound | Self::UserNotFound | Self::FeatureFlagNotFound => {
+                StatusCode::NOT_FOUND
+            }
+            Self::ServiceUnavailable
+            | Self::DatabaseBusy
+            | Self::MaintenanceMode
+            | Self::ReadOnlyMode
+            | Self::ServerOverloaded => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InternalError
+            | Self::DatabaseError
+            | Self::ConfigError
+            | Self::IoError
+            | Self::SerializationError
+            | Self::AddrParseError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "The request is malformed",
+            Self::Unauthorized => "Authentication is required",
+            Self::Forbidden => "The caller is not allowed to perform this action",
+            Self::NotFound => "The requested resource does not exist",
+            Self::ServiceUnavailable => "The service is temporarily unavailable",
+            Self::InternalError => "An unexpected server error occurred",
+            Self::DatabaseError => "A database error occurred",
+            Self::DatabaseBusy => "The database is busy, retry later",
+            Self::ConfigError => "The server configuration could not be read",
+            Self::IoError => "A server I/O error occurred",
+            Self::SerializationError => "Data could not be serialized or deserialized",
+            Self::AddrParseError => "A network address could not be parsed",
+            Self::InvalidId => "The id is not a valid UUID",
+            Self::InvalidLogFilter => "The log filter directive is invalid",
+            Self::InvalidFeatureFlag => "The feature flag rule is invalid",
+            Self::InvalidBody => {
+                "The JSON request body is malformed or does not match the expected shape"
+            }
+            Self::InvalidPath => "A path parameter could not be parsed",
+            Self::InvalidQuery => "The query string could not be parsed",
+            Self::UnsupportedMediaType => "The request body must be sent as application/json",
+            Self::PayloadTooLarge => "The request body is too large",
+            Self::AuthTokenMissing => "No bearer token was sent",
+            Self::AuthTokenInvalid => "The bearer token is malformed or its signature is invalid",
+            Self::AuthTokenExpired => "The bearer token has expired",
+            Self::AdminRequired => "The admin role is required",
+            Self::IpDenied => "Requests from this address are not allowed",
+            Self::MaintenanceMode => "The service is under maintenance",
+            Self::ReadOnlyMode => "The service is read-only, writes are rejected",
+            Self::ServerOverloaded => "Too many requests in flight, retry later",
+            Self::UserNotFound => "The user does not exist",
+            Self::FeatureFlagNotFound => "The feature flag is not defined in the configuration",
+        }
+    }
+}
+
+/// 错误码目录中的一项
+#[derive(Debug, Serialize)]
+pub struct ErrorCodeInfo {
+    pub code: ErrorCode,
+    pub status: u16,
+    pub description: &'static str,
+}
+
+/// 所有错误码及其 HTTP 状态码和说明
+pub fn catalog() -> Vec<ErrorCodeInfo> {
+    ErrorCode::iter()
+        .map(|code| ErrorCodeInfo {
+            code,
+            status: code.status().as_u16(),
+            description: code.description(),
+        })
+        .collect()
+}
@@ -1,11 +1,41 @@
+use std::{error::Error as _, future::Future};
+
 use axum::{
-    http::StatusCode,
+    http::{header::RETRY_AFTER, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
+use serde_json::Value;
 use thiserror::Error;
-use tracing::error;
+use tracing::{error, warn};
+
+pub mod code;
+pub mod rejection;
+pub mod request_id;
+
+pub use code::ErrorCode;
+
+/// 数据库连接池繁忙时返回给客户端的 `Retry-After` 秒数
+const DB_RETRY_AFTER_SECS: u64 = 1;
+
+tokio::task_local! {
+    /// 当前请求是否把内部错误的细节返回给客户端，由 middleware 按应用的 profile 设置
+    static EXPOSE_INTERNAL_ERRORS: bool;
+}
+
+/// 在给定设置下执行 future，其中产生的错误响应按该设置决定是否返回内部错误的细节
+pub async fn expose_internal_errors_scope<F>(expose: bool, fut: F) -> F::Output
+where
+    F: Future,
+{
+    EXPOSE_INTERNAL_ERRORS.scope(expose, fut).await
+}
+
+/// 是否向客户端返回内部错误的细节，不在请求上下文中时不返回
+pub fn expose_internal_errors() -> bool {
+    EXPOSE_INTERNAL_ERRORS.try_with(|expose| *expose).unwrap_or(false)
+}
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -24,6 +54,12 @@ pub enum AppError {
     #[error("Unauthorized error: {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -34,29 +70,245 @@ pub enum AppError {
     AddrParseError(#[from] std::net::AddrParseError),
 
     #[error("Internal error: {0}")]
-    Internal(#[from] anyhow::Error),
+    Internal(#[source] anyhow::Error),
+
+    /// 带具体错误码的错误，`details` 为返回给客户端的结构化信息
+    #[error("{message}")]
+    Coded { code: ErrorCode, message: String, details: Option<Value> },
+
+    /// 附带结构化信息的其他错误，错误码、状态码和响应头与原错误一致
+    #[error("{error}")]
+    Detailed { error: Box<AppError>, details: Value },
+}
+
+impl AppError {
+    pub fn coded(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError::Coded { code, message: message.into(), details: None }
+    }
+
+    /// 附加结构化信息，已有的结构化信息会被替换
+    pub fn with_details(self, details: Value) -> Self {
+        match self {
+            AppError::Coded { code, message, .. } => {
+                AppError::Coded { code, message, details: Some(details) }
+            }
+            AppError::Detailed { error, .. } => AppError::Detailed { error, details },
+            other => AppError::Detailed { error: Box::new(other), details },
+        }
+    }
+
+    /// 去掉 `Detailed` 包装后的原始错误
+    fn base(&self) -> &AppError {
+        match self {
+            AppError::Detailed { error, .. } => error.base(),
+            other => other,
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            AppError::Coded { details, .. } => details.clone(),
+            AppError::Detailed { details, .. } => Some(details.clone()),
+            _ => None,
+        }
+    }
+
+    /// 服务端错误(5xx)，细节可能包含 SQL、约束名等不应暴露的信息
+    pub fn is_internal(&self) -> bool {
+        self.code().status().is_server_error()
+    }
+
+    /// 隐藏细节时是否仍然返回原始信息，维护模式、过载等 503 错误的信息本来就是写给客户端的
+    fn is_public(&self) -> bool {
+        matches!(self.base(), AppError::Coded { code, .. } if code.status() == StatusCode::SERVICE_UNAVAILABLE)
+    }
+
+    /// 错误及其所有 source 组成的错误链
+    fn chain(&self) -> String {
+        let base = self.base();
+        let mut chain = base.to_string();
+        let mut source = base.source();
+        while let Some(e) = source {
+            chain.push_str(": ");
+            chain.push_str(&e.to_string());
+            source = e.source();
+        }
+        chain
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::DbError(sqlx::Error::PoolTimedOut) => ErrorCode::DatabaseBusy,
+            AppError::DbError(_) => ErrorCode::DatabaseError,
+            AppError::ConfigReadError => ErrorCode::ConfigError,
+            AppError::NotFound => ErrorCode::NotFound,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::ServiceUnavailable(_) => ErrorCode::ServiceUnavailable,
+            AppError::IoError(_) => ErrorCode::IoError,
+            AppError::SerdeError(_) => ErrorCode::SerializationError,
+            AppError::AddrParseError(_) => ErrorCode::AddrParseError,
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::Coded { code, .. } => *code,
+            AppError::Detailed { error, .. } => error.code(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    /// repository 层返回的 anyhow 错误中如果是 sqlx 错误，还原为 `DbError`
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<sqlx::Error>() {
+            Ok(db) => AppError::DbError(db),
+            Err(e) => AppError::Internal(e),
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct ErrorResponse {
     code: u16,
+    error_code: ErrorCode,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    /// 内部错误的编号，服务端日志中以该编号记录完整错误链
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_id: Option<String>,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            AppError::DbError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            AppError::ConfigReadError => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            AppError::NotFound => (StatusCode::NOT_FOUND, self.to_string()),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
-            AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            AppError::IoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            AppError::SerdeError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            AppError::AddrParseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        let error_code = self.code();
+        let status = error_code.status();
+        let base = self.base();
+        let mut message = match base {
+            AppError::DbError(sqlx::Error::PoolTimedOut) => {
+                "Database is busy, please retry later".to_string()
+            }
+            AppError::BadRequest(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::ServiceUnavailable(msg) => msg.clone(),
+            AppError::Coded { message, .. } => message.clone(),
+            _ => base.to_string(),
         };
-        let body = Json(ErrorResponse { code: status.as_u16(), message });
-        (status, body).into_response()
+        let mut details = self.details();
+        let request_id = request_id::current();
+
+        let error_id = self.is_internal().then(|| uuid::Uuid::new_v4().to_string());
+        if let Some(error_id) = &error_id {
+            let request_id = request_id.as_deref().unwrap_or_default();
+            // 维护模式、过载等暂时不可用属于预期情况，不按错误记录
+            if status == StatusCode::SERVICE_UNAVAILABLE {
+                warn!(error_id = %error_id, request_id, error_code = %error_code, "service unavailable: {}", self.chain());
+            } else {
+                error!(error_id = %error_id, request_id, error_code = %error_code, "internal error: {}", self.chain());
+            }
+            // 隐藏细节时只返回错误码的说明
+            if !expose_internal_errors() && !self.is_public() {
+                message = error_code.description().to_string();
+                details = None;
+            }
+        }
+
+        let body = Json(ErrorResponse {
+            code: status.as_u16(),
+            error_code,
+            message,
+            details,
+            request_id,
+            error_id,
+        });
+        let mut response = (status, body).into_response();
+        if let AppError::DbError(sqlx::Error::PoolTimedOut) = self.base() {
+            response.headers_mut().insert(RETRY_AFTER, DB_RETRY_AFTER_SECS.into());
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use serde_json::json;
+
+    use super::*;
+
+    async fn body(response: Response) -> Value {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn details_keep_the_original_status_and_headers() {
+        let error =
+            AppError::DbError(sqlx::Error::PoolTimedOut).with_details(json!({ "pool": "main" }));
+        assert_eq!(error.code(), ErrorCode::DatabaseBusy);
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+        let body = body(response).await;
+        assert_eq!(body["error_code"], "DATABASE_BUSY");
+        assert!(body["error_id"].is_string());
+    }
+
+    #[tokio::test]
+    async fn details_replace_previous_details() {
+        let error = AppError::NotFound.with_details(json!(1)).with_details(json!(2));
+        assert!(
+            matches!(&error, AppError::Detailed { error, .. } if matches!(**error, AppError::NotFound))
+        );
+
+        let error =
+            AppError::coded(ErrorCode::InvalidId, "invalid id").with_details(json!({ "id": "x" }));
+        let body = body(error.into_response()).await;
+        assert_eq!(body["error_code"], "INVALID_ID");
+        assert_eq!(body["details"], json!({ "id": "x" }));
+    }
+
+    #[test]
+    fn server_errors_are_internal_whatever_the_variant() {
+        assert!(AppError::coded(ErrorCode::DatabaseError, "duplicate key users_email_key")
+            .is_internal());
+        assert!(AppError::Internal(anyhow::anyhow!("boom")).is_internal());
+        assert!(AppError::DbError(sqlx::Error::PoolTimedOut).is_internal());
+        assert!(!AppError::NotFound.with_details(json!(1)).is_internal());
+        assert!(!AppError::coded(ErrorCode::InvalidId, "invalid id").is_internal());
+    }
+
+    #[tokio::test]
+    async fn coded_server_errors_are_masked_by_default() {
+        let error = AppError::coded(ErrorCode::DatabaseError, "duplicate key users_email_key")
+            .with_details(json!({ "constraint": "users_email_key" }));
+        let body = body(error.into_response()).await;
+        assert_eq!(body["error_code"], "DATABASE_ERROR");
+        assert_eq!(body["message"], ErrorCode::DatabaseError.description());
+        assert!(body.get("details").is_none());
+        assert!(body["error_id"].is_string());
+    }
+
+    #[tokio::test]
+    async fn service_state_messages_stay_visible() {
+        let error = AppError::coded(ErrorCode::MaintenanceMode, "back at 10:00");
+        let body = body(error.into_response()).await;
+        assert_eq!(body["message"], "back at 10:00");
+    }
+
+    #[tokio::test]
+    async fn details_are_exposed_only_inside_an_exposing_scope() {
+        let error = || AppError::coded(ErrorCode::DatabaseError, "duplicate key users_email_key");
+        let exposed =
+            expose_internal_errors_scope(true, async { body(error().into_response()).await }).await;
+        assert_eq!(exposed["message"], "duplicate key users_email_key");
+
+        let hidden =
+            expose_internal_errors_scope(false, async { body(error().into_response()).await })
+                .await;
+        assert_eq!(hidden["message"], ErrorCode::DatabaseError.description());
     }
 }
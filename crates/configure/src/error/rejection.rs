@@ -0,0 +1,35 @@
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::StatusCode,
+};
+
+use super::{AppError, ErrorCode};
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        let code = match rejection.status() {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            _ => ErrorCode::InvalidBody,
+        };
+        AppError::coded(code, rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        // 路由与提取器不匹配等属于服务端的问题，保留原来的 500
+        let code = if rejection.status().is_server_error() {
+            ErrorCode::InternalError
+        } else {
+            ErrorCode::InvalidPath
+        };
+        AppError::coded(code, rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::coded(ErrorCode::InvalidQuery, rejection.body_text())
+    }
+}
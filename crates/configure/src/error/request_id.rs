@@ -0,0 +1,19 @@
+use std::future::Future;
+
+tokio::task_local! {
+    /// 当前请求的 request id，由 middleware 在处理请求时设置
+    static REQUEST_ID: String;
+}
+
+/// 在给定 request id 的上下文中执行 future
+pub async fn scope<F>(request_id: String, fut: F) -> F::Output
+where
+    F: Future,
+{
+    REQUEST_ID.scope(request_id, fut).await
+}
+
+/// 获取当前请求的 request id，不在请求上下文中时返回 None
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
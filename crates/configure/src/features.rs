@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// 功能开关配置，对应配置文件中的 `[features]`
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct FeaturesConfig {
+    /// 数据库中的运行时覆盖缓存的秒数
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// 功能开关的定义和默认规则，键为开关名，各 profile 的配置文件可以给出不同的默认值
+    #[serde(default)]
+    pub flags: BTreeMap<String, FlagConfig>,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    5
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self { cache_ttl_secs: default_cache_ttl_secs(), flags: BTreeMap::new() }
+    }
+}
+
+/// 一个功能开关的定义
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct FlagConfig {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub percentage: Option<u8>,
+    #[serde(default)]
+    pub users: Vec<Uuid>,
+    #[serde(default)]
+    pub tenants: Vec<String>,
+}
+
+impl FlagConfig {
+    /// 配置中的默认规则
+    pub fn rule(&self) -> FlagRule {
+        FlagRule {
+            enabled: self.enabled,
+            percentage: self.percentage,
+            users: self.users.clone(),
+            tenants: self.tenants.clone(),
+        }
+    }
+}
+
+/// 功能开关的生效规则，管理接口的请求体，拼错的字段名会被拒绝而不是静默忽略
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlagRule {
+    /// 总开关，关闭时对所有人关闭，必须显式给出
+    pub enabled: bool,
+    /// 按用户灰度的百分比(0-100)，没有用户时按租户；不设置时配置了 users 或 tenants
+    /// 则只对它们开启，否则对所有人开启
+    #[serde(default)]
+    pub percentage: Option<u8>,
+    /// 始终开启的用户
+    #[serde(default)]
+    pub users: Vec<Uuid>,
+    /// 始终开启的租户
+    #[serde(default)]
+    pub tenants: Vec<String>,
+}
+
+/// 判断功能开关时使用的请求信息
+#[derive(Debug, Clone, Default)]
+pub struct FlagContext {
+    pub user_id: Option<Uuid>,
+    pub tenant: Option<String>,
+}
+
+impl FlagRule {
+    /// 判断开关 `name` 对指定用户/租户是否开启，同一用户的灰度结果保持稳定
+    pub fn evaluate(&self, name: &str, ctx: &FlagContext) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let targeted = ctx.user_id.is_some_and(|id| self.users.contains(&id))
+            || ctx.tenant.as_ref().is_some_and(|tenant| self.tenants.contains(tenant));
+        if targeted {
+            return true;
+        }
+        match self.percentage {
+            None => self.users.is_empty() && self.tenants.is_empty(),
+            Some(percentage) => {
+                let key = ctx.user_id.map(|id| id.to_string()).or_else(|| ctx.tenant.clone());
+                match key {
+                    Some(key) => bucket(name, &key) < percentage,
+                    None => percentage >= 100,
+                }
+            }
+        }
+    }
+}
+
+/// 把开关名和用户映射到 0-99 的桶，使用 FNV-1a 保证跨版本、跨进程稳定
+fn bucket(name: &str, key: &str) -> u8 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in name.bytes().chain([b':']).chain(key.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(n: u128) -> FlagContext {
+        FlagContext { user_id: Some(Uuid::from_u128(n)), tenant: None }
+    }
+
+    fn rule(percentage: Option<u8>) -> FlagRule {
+        FlagRule { enabled: true, percentage, ..Default::default() }
+    }
+
+    #[test]
+    fn bucket_is_stable_and_in_range() {
+        // FNV-1a 的结果固定，修改算法会让已经灰度的用户重新分桶
+        assert_eq!(bucket("new_checkout", "alice"), 12);
+        for n in 0..1000 {
+            assert!(bucket("flag", &n.to_string()) < 100);
+        }
+    }
+
+    #[test]
+    fn disabled_rule_is_off_even_for_targeted_users() {
+        let rule =
+            FlagRule { enabled: false, users: vec![Uuid::from_u128(1)], ..Default::default() };
+        assert!(!rule.evaluate("flag", &user(1)));
+    }
+
+    #[test]
+    fn without_percentage_targets_only_listed_users_and_tenants() {
+        assert!(rule(None).evaluate("flag", &FlagContext::default()));
+
+        let targeted = FlagRule { tenants: vec!["acme".to_string()], ..rule(None) };
+        let acme =
+            FlagContext { user_id: Some(Uuid::from_u128(1)), tenant: Some("acme".to_string()) };
+        assert!(targeted.evaluate("flag", &acme));
+        assert!(!targeted.evaluate("flag", &user(1)));
+    }
+
+    #[test]
+    fn percentage_rolls_out_to_a_stable_share_of_users() {
+        assert!(!rule(Some(0)).evaluate("flag", &user(1)));
+        assert!(rule(Some(100)).evaluate("flag", &user(1)));
+        // 没有用户和租户时无法分桶，只有 100% 才开启
+        assert!(!rule(Some(99)).evaluate("flag", &FlagContext::default()));
+
+        let enabled = (0..1000).filter(|n| rule(Some(30)).evaluate("flag", &user(*n))).count();
+        assert!((250..350).contains(&enabled), "{enabled} of 1000 users enabled");
+        let user = user(42);
+        assert_eq!(rule(Some(30)).evaluate("flag", &user), rule(Some(30)).evaluate("flag", &user));
+    }
+
+    #[test]
+    fn rule_requires_enabled_and_rejects_unknown_fields() {
+        let parsed: FlagRule = serde_json::from_str(r#"{"enabled":true,"percentage":25}"#).unwrap();
+        assert_eq!(parsed, rule(Some(25)));
+        assert!(serde_json::from_str::<FlagRule>(r#"{"percentage":25}"#).is_err());
+        assert!(serde_json::from_str::<FlagRule>(r#"{"enabled":true,"percentag":25}"#).is_err());
+    }
+}
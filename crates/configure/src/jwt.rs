@@ -1,7 +1,14 @@
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+use crate::secret::Secret;
+
+/// 管理员角色名，签发令牌时写入 `role` 声明
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct JwtConfig {
-    pub secret: String,
+    pub secret: Secret,
     pub expired: i64,
 }
@@ -0,0 +1,151 @@
+use std::{collections::BTreeMap, fmt};
+
+use config::{
+    builder::DefaultState, Config, ConfigBuilder, ConfigError, Map, Source, Value, ValueKind,
+};
+
+use crate::env::{get_env_overrides, get_env_source, get_file_env_overrides};
+
+/// 没有任何配置源设置、使用代码中默认值的配置项
+pub const DEFAULT_SOURCE: &str = "default value";
+
+/// 按顺序合并的配置源，后面的覆盖前面的，同时记录每个配置项最终来自哪个配置源
+pub(crate) struct ConfigLayers {
+    builder: ConfigBuilder<DefaultState>,
+    sources: ConfigSources,
+}
+
+impl ConfigLayers {
+    pub fn new() -> Self {
+        Self { builder: Config::builder(), sources: ConfigSources::default() }
+    }
+
+    /// 添加一个配置源，`name` 用于报告配置项的来源
+    pub fn add(
+        mut self,
+        name: String,
+        source: Box<dyn Source + Send + Sync>,
+    ) -> Result<Self, ConfigError> {
+        for key in flatten(source.collect()?) {
+            self.sources.values.insert(key, name.clone());
+        }
+        self.sources.layers.push(name);
+        self.builder = self.builder.add_source(vec![source]);
+        Ok(self)
+    }
+
+    /// 添加 `{prefix}__*` 环境变量和 `{prefix}__*_FILE` 指向的文件
+    pub fn add_env(mut self, prefix: &str) -> Result<Self, ConfigError> {
+        for env in get_env_overrides(prefix) {
+            self.sources.values.insert(env.key, format!("env {}", env.var));
+        }
+        self.builder = self.builder.add_source(get_env_source(prefix));
+        for env in get_file_env_overrides(prefix)? {
+            self.sources.values.insert(env.key.clone(), format!("env {}", env.var));
+            self.builder = self.builder.set_override(env.key, env.value)?;
+        }
+        self.sources.layers.push(format!("env {}__*", prefix.to_uppercase()));
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<(Config, ConfigSources), ConfigError> {
+        Ok((self.builder.build()?, self.sources))
+    }
+}
+
+/// 参与合并的配置源，以及每个配置项最终来自哪个配置源
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    /// 按合并顺序排列的配置源
+    pub layers: Vec<String>,
+    /// 配置项路径到配置源的映射，只包含配置源中出现的配置项
+    values: BTreeMap<String, String>,
+}
+
+impl ConfigSources {
+    pub(crate) fn insert(&mut self, path: &str, source: impl Into<String>) {
+        self.values.insert(path.to_string(), source.into());
+    }
+
+    /// 配置项的来源，没有配置源设置时为 [`DEFAULT_SOURCE`]
+    pub fn source_of(&self, path: &str) -> &str {
+        self.values.get(path).map_or(DEFAULT_SOURCE, String::as_str)
+    }
+
+    /// 生效配置中每个配置项的来源，`effective` 为序列化后的配置
+    pub fn explain(&self, effective: &serde_json::Value) -> Vec<(String, String)> {
+        let mut paths = Vec::new();
+        leaf_paths("", effective, &mut paths);
+        paths
+            .into_iter()
+            .map(|path| {
+                let source = self.source_of(&path).to_string();
+                (path, source)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ConfigSources {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.layers.join(" -> "))
+    }
+}
+
+/// 配置源中所有叶子配置项的路径，数组视为一个配置项
+fn flatten(values: Map<String, Value>) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut stack: Vec<(String, Value)> = values.into_iter().collect();
+    while let Some((key, value)) = stack.pop() {
+        match value.kind {
+            ValueKind::Table(table) => stack
+                .extend(table.into_iter().map(|(child, value)| (format!("{key}.{child}"), value))),
+            _ => keys.push(key),
+        }
+    }
+    keys
+}
+
+fn leaf_paths(prefix: &str, value: &serde_json::Value, paths: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                leaf_paths(&path, value, paths);
+            }
+        }
+        _ => paths.push(prefix.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_overrides_keys_with_underscores() {
+        // 使用单独的前缀，避免影响其他测试
+        let secret = std::env::temp_dir().join(format!("layers-secret-{}", std::process::id()));
+        std::fs::write(&secret, "from-file\n").unwrap();
+        std::env::set_var("LAYERSTEST__SERVER__MAX_IN_FLIGHT", "5");
+        std::env::set_var("LAYERSTEST__DATABASE__DATABASE_NAME", "orders");
+        std::env::set_var("LAYERSTEST__JWT__SECRET_FILE", &secret);
+
+        let (config, sources) = ConfigLayers::new().add_env("LAYERSTEST").unwrap().build().unwrap();
+        assert_eq!(config.get_int("server.max_in_flight").unwrap(), 5);
+        assert_eq!(config.get_string("database.database_name").unwrap(), "orders");
+        assert_eq!(config.get_string("jwt.secret").unwrap(), "from-file");
+        assert!(config.get_int("server.max").is_err());
+
+        assert_eq!(
+            sources.source_of("server.max_in_flight"),
+            "env LAYERSTEST__SERVER__MAX_IN_FLIGHT"
+        );
+        assert_eq!(
+            sources.source_of("database.database_name"),
+            "env LAYERSTEST__DATABASE__DATABASE_NAME"
+        );
+        assert_eq!(sources.source_of("jwt.secret"), "env LAYERSTEST__JWT__SECRET_FILE");
+        assert_eq!(sources.to_string(), "env LAYERSTEST__*");
+    }
+}
@@ -1,68 +1,166 @@
+pub mod config_dir;
 pub mod database;
 pub mod env;
 pub mod error;
+pub mod features;
 pub mod jwt;
+pub mod layers;
+pub mod log_config;
+pub mod log_filter;
+pub mod log_redact;
 pub mod log_tracing;
+pub mod maintenance;
+pub mod metrics;
+pub mod metrics_recorder;
+pub mod network;
+pub mod otlp;
 pub mod profile;
+pub mod redact;
+pub mod reload;
+pub mod schema;
+pub mod secret;
 pub mod server;
+pub mod validate;
 
 use std::path::PathBuf;
 
 use anyhow::Result;
 use config::ConfigError;
+pub use config_dir::get_root_dir;
+use config_dir::ConfigDir;
 use database::DatabaseConfig;
-use env::{get_env_source, get_profile};
+use env::get_profile;
+use features::FeaturesConfig;
 use jwt::JwtConfig;
-use once_cell::sync::Lazy;
+use layers::ConfigLayers;
+use log_config::TracingConfig;
+use metrics::MetricsConfig;
+use network::NetworkConfig;
+use otlp::OtlpConfig;
 use profile::Profile;
-use serde::Deserialize;
+use redact::MASK;
+use schemars::JsonSchema;
+use secret::{resolve_file_refs, Secret};
+use serde::{Deserialize, Serialize};
 use server::ServerConfig;
 use tracing::info;
+use validate::ConfigReport;
 
-pub static CONFIG: Lazy<AppConfig> = Lazy::new(|| AppConfig::read().unwrap());
+/// 读取配置的选项，通常来自命令行参数，未指定的项以环境变量为准
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// 配置目录，优先于 `APP_CONFIG_DIR`
+    pub config_dir: Option<PathBuf>,
+    /// 未知的配置项视为错误，为 false 时以 `APP_CONFIG_STRICT` 为准
+    pub strict: bool,
+}
+
+impl LoadOptions {
+    pub fn is_strict(&self) -> bool {
+        self.strict || schema::strict_from_env()
+    }
+}
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct AppConfig {
+    /// 由 `ENVIRONMENT` 环境变量选择，配置文件中的值会被忽略
+    #[serde(default)]
     pub profile: Profile,
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub jwt: JwtConfig,
+    #[serde(default)]
+    pub tracing: TracingConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub features: FeaturesConfig,
 }
 
 impl AppConfig {
+    /// 按默认选项读取配置，配置目录和严格模式只由环境变量决定
     pub fn read() -> Result<AppConfig, ConfigError> {
-        let config_dir =
-            get_root_dir().map_err(|e| ConfigError::Message(e.to_string()))?.join("setting");
-
-        let env_source = get_env_source("APP");
-        info!("config dir: {:#?}", config_dir);
-        let profile = get_profile()?;
-        let profile_filename = format!("{profile}.toml");
-        info!("running in {:?} mode", profile);
+        Self::load(&LoadOptions::default()).map(|(config, _)| config)
+    }
 
-        let config = config::Config::builder()
-            .add_source(config::File::with_name(&format!(
-                "{}/default.toml",
-                config_dir.to_string_lossy()
-            )))
-            .add_source(config::File::with_name(&format!(
-                "{}/{}",
-                config_dir.to_string_lossy(),
-                profile_filename
-            )))
-            .add_source(env_source)
-            .build()?;
-        config.try_deserialize()
+    /// 读取并检查配置，存在错误时返回包含所有问题的错误，只有警告时随配置一起返回
+    pub fn load(options: &LoadOptions) -> Result<(AppConfig, ConfigReport), ConfigError> {
+        let (app_config, report) = Self::load_unchecked(options)?;
+        if report.has_errors() {
+            return Err(ConfigError::Message(report.to_string()));
+        }
+        Ok((app_config, report))
     }
-}
 
-pub fn get_root_dir() -> Result<PathBuf, ConfigError> {
-    let mut current = std::env::current_dir().map_err(|e| ConfigError::Message(e.to_string()))?;
-    while current.parent().is_some() {
-        if current.join("Cargo.lock").exists() {
-            return Ok(current);
+    /// 读取并检查配置，检查发现的错误只记录在报告中，供 `config check` 输出
+    pub fn load_unchecked(options: &LoadOptions) -> Result<(AppConfig, ConfigReport), ConfigError> {
+        let config_dir = ConfigDir::discover(options.config_dir.as_deref())?;
+        info!("config dir: {:?}", config_dir);
+        let profile = match get_profile()? {
+            Some(profile) => profile,
+            None => config_dir.default_profile()?,
+        };
+        let profile = config_dir.resolve_profile(profile)?;
+        info!("running in {} mode", profile);
+
+        // default -> 继承的 profile(从远到近) -> profile -> local -> 环境变量
+        let mut layers = ConfigLayers::new();
+        let names = std::iter::once("default")
+            .chain(profile.ancestors().iter().rev().map(String::as_str))
+            .chain([profile.name()]);
+        for name in names {
+            if let Some((source_name, source)) = config_dir.file(name, true)? {
+                layers = layers.add(source_name, source)?;
+            }
         }
-        current = current.parent().unwrap().to_path_buf();
+        if let Some((source_name, source)) = config_dir.file("local", false)? {
+            layers = layers.add(source_name, source)?;
+        }
+        let (config, sources) = layers.add_env("APP")?.build()?;
+        info!("config sources: {}", sources);
+        // `file:` 引用在所有来源合并之后解析
+        let config = resolve_file_refs(config)?;
+
+        let mut report = ConfigReport { sources, ..Default::default() };
+        let profile_source = match std::env::var_os("ENVIRONMENT") {
+            Some(_) => "env ENVIRONMENT",
+            None => layers::DEFAULT_SOURCE,
+        };
+        report.sources.insert("profile", profile_source);
+        let mut unknown_keys = Vec::new();
+        let mut app_config: AppConfig = serde_ignored::deserialize(config, |path| {
+            let path = path.to_string();
+            // `extends` 只用于查找继承的配置文件
+            if path != "extends" {
+                unknown_keys.push(path);
+            }
+        })?;
+        app_config.profile = profile;
+        let strict = options.is_strict();
+        for key in unknown_keys {
+            let source = report.sources.source_of(&key).to_string();
+            if strict {
+                report.error(&key, format!("unknown key in {source}"));
+            } else {
+                report.warning(&key, format!("unknown key in {source}, ignored"));
+            }
+        }
+        app_config.validate(&mut report);
+        Ok((app_config, report))
+    }
+
+    /// 敏感配置替换为占位符后的副本，用于打印配置
+    pub fn redacted(&self) -> AppConfig {
+        let mask = || Secret::new(MASK);
+        let mut config = self.clone();
+        config.database.url = config.database.url.as_ref().map(|_| mask());
+        config.database.password = mask();
+        config.jwt.secret = mask();
+        config
     }
-    Err(ConfigError::Message("Cannot find Cargo.toml in any parent directory".into()))
 }
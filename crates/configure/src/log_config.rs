@@ -0,0 +1,143 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::redact::RedactConfig;
+
+/// 日志配置，对应配置文件中的 `[tracing]`
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct TracingConfig {
+    /// 默认日志级别，设置了 `RUST_LOG` 环境变量时以环境变量为准
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// 附加的 `EnvFilter` 规则，例如 `sqlx=warn`、`tower_http=debug`
+    #[serde(default)]
+    pub directives: Vec<String>,
+    #[serde(default)]
+    pub console: ConsoleConfig,
+    #[serde(default)]
+    pub file: FileConfig,
+    /// 日志脱敏规则
+    #[serde(default)]
+    pub redact: RedactConfig,
+}
+
+impl TracingConfig {
+    /// 由日志级别和附加规则拼成的过滤规则
+    pub fn filter_directive(&self) -> String {
+        std::iter::once(self.log_level.as_str())
+            .chain(self.directives.iter().map(String::as_str))
+            .filter(|d| !d.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            log_level: default_log_level(),
+            directives: Vec::new(),
+            console: ConsoleConfig::default(),
+            file: FileConfig::default(),
+            redact: RedactConfig::default(),
+        }
+    }
+}
+
+/// 控制台输出配置
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ConsoleConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: ConsoleFormat,
+    /// 是否输出 ANSI 颜色
+    #[serde(default = "default_enabled")]
+    pub ansi: bool,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self { enabled: true, format: ConsoleFormat::default(), ansi: true }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleFormat {
+    Pretty,
+    #[default]
+    Compact,
+    Json,
+}
+
+/// 文件输出配置
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct FileConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 日志目录，相对路径基于项目根目录，不在 Cargo workspace 中运行时基于当前目录
+    #[serde(default = "default_dir")]
+    pub dir: String,
+    /// 日志文件名前缀
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default)]
+    pub rotation: FileRotation,
+    /// 最多保留的日志文件数，不配置则不清理
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    #[serde(default)]
+    pub format: FileFormat,
+}
+
+impl Default for FileConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: default_dir(),
+            prefix: default_prefix(),
+            rotation: FileRotation::default(),
+            max_files: None,
+            format: FileFormat::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    #[default]
+    Bunyan,
+    Json,
+    Text,
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_dir() -> String {
+    "logs".to_string()
+}
+
+fn default_prefix() -> String {
+    "app.log".to_string()
+}
@@ -0,0 +1,180 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::error::{AppError, ErrorCode};
+
+/// 运行时可替换的日志过滤器
+#[derive(Clone)]
+pub struct LogFilterHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+    state: Arc<Mutex<FilterState>>,
+    /// 未挂载到 subscriber 时持有过滤层，保证 handle 可用
+    _detached: Option<Arc<reload::Layer<EnvFilter, Registry>>>,
+}
+
+struct FilterState {
+    /// 长期生效的过滤规则，临时规则过期后恢复到它
+    base: String,
+    expires_at: Option<Instant>,
+    revert_task: Option<JoinHandle<()>>,
+    /// 每次替换规则时递增，过期任务只恢复自己设置的临时规则
+    generation: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogFilterStatus {
+    /// 当前生效的过滤规则
+    pub directive: String,
+    /// 临时规则过期后恢复的过滤规则
+    pub base: String,
+    /// 临时规则剩余的有效秒数
+    pub expires_in_secs: Option<u64>,
+}
+
+impl LogFilterHandle {
+    pub(crate) fn new(handle: reload::Handle<EnvFilter, Registry>, base: String) -> Self {
+        let state = FilterState { base, expires_at: None, revert_task: None, generation: 0 };
+        Self { handle, state: Arc::new(Mutex::new(state)), _detached: None }
+    }
+
+    /// 未挂载到 subscriber 的过滤器，用于没有初始化日志系统的实例，例如测试
+    pub fn detached(base: &str) -> Self {
+        let filter = EnvFilter::try_new(base).unwrap_or_else(|_| EnvFilter::new("info"));
+        let (layer, handle) = reload::Layer::new(filter);
+        let mut this = Self::new(handle, base.to_string());
+        this._detached = Some(Arc::new(layer));
+        this
+    }
+
+    pub fn status(&self) -> LogFilterStatus {
+        let state = self.state.lock().unwrap();
+        LogFilterStatus {
+            directive: self.current(),
+            base: state.base.clone(),
+            expires_in_secs: state
+                .expires_at
+                .map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+        }
+    }
+
+    /// 当前生效的过滤规则
+    pub fn current(&self) -> String {
+        self.handle.with_current(|filter| filter.to_string()).unwrap_or_default()
+    }
+
+    /// 替换过滤规则，指定 ttl 时到期后自动恢复为之前长期生效的规则
+    ///
+    /// 持有状态锁替换过滤器，并发调用按加锁顺序生效，生效的规则与 [`Self::status`] 一致
+    pub fn set(&self, directive: &str, ttl: Option<Duration>) -> Result<(), AppError> {
+        let filter = EnvFilter::try_new(directive).map_err(|e| {
+            AppError::coded(ErrorCode::InvalidLogFilter, format!("invalid log filter: {e}"))
+        })?;
+
+        let mut state = self.state.lock().unwrap();
+        self.reload(filter)?;
+        state.generation += 1;
+        if let Some(task) = state.revert_task.take() {
+            task.abort();
+        }
+        match ttl {
+            Some(ttl) => {
+                state.expires_at = Some(Instant::now() + ttl);
+                let this = self.clone();
+                let generation = state.generation;
+                state.revert_task = Some(tokio::spawn(async move {
+                    tokio::time::sleep(ttl).await;
+                    this.revert(generation);
+                }));
+            }
+            None => {
+                state.base = directive.to_string();
+                state.expires_at = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// 恢复长期生效的规则；等锁期间规则又被替换过时什么都不做
+    fn revert(&self, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        state.expires_at = None;
+        state.revert_task = None;
+        let result = EnvFilter::try_new(&state.base)
+            .map_err(|e| AppError::Internal(e.into()))
+            .and_then(|filter| self.reload(filter));
+        match result {
+            Ok(()) => info!(directive = %state.base, "temporary log filter expired, reverted"),
+            Err(e) => error!("failed to revert log filter: {}", e),
+        }
+    }
+
+    fn reload(&self, filter: EnvFilter) -> Result<(), AppError> {
+        self.handle.reload(filter).map_err(|e| AppError::Internal(e.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(directive: &str) -> String {
+        EnvFilter::new(directive).to_string()
+    }
+
+    #[tokio::test]
+    async fn temporary_filter_reverts_to_base() {
+        let filter = LogFilterHandle::detached("info");
+        filter.set("debug", Some(Duration::from_millis(20))).unwrap();
+        assert_eq!(filter.current(), normalized("debug"));
+        assert!(filter.status().expires_in_secs.is_some());
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let status = filter.status();
+        assert_eq!(status.directive, normalized("info"));
+        assert_eq!(status.expires_in_secs, None);
+    }
+
+    #[tokio::test]
+    async fn permanent_set_survives_pending_revert() {
+        let filter = LogFilterHandle::detached("info");
+        filter.set("debug", Some(Duration::from_millis(20))).unwrap();
+        let stale = filter.state.lock().unwrap().generation;
+        filter.set("warn", None).unwrap();
+
+        // 已经醒来、正在等锁的过期任务无法被 abort，只能靠 generation 判断
+        filter.revert(stale);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let status = filter.status();
+        assert_eq!(status.directive, normalized("warn"));
+        assert_eq!(status.base, "warn");
+    }
+
+    #[tokio::test]
+    async fn newer_ttl_is_not_cut_short_by_older_one() {
+        let filter = LogFilterHandle::detached("info");
+        filter.set("debug", Some(Duration::from_millis(20))).unwrap();
+        let stale = filter.state.lock().unwrap().generation;
+        filter.set("trace", Some(Duration::from_secs(60))).unwrap();
+        filter.revert(stale);
+        assert_eq!(filter.current(), normalized("trace"));
+        assert!(filter.status().expires_in_secs.is_some());
+    }
+
+    #[test]
+    fn rejects_invalid_directive() {
+        let filter = LogFilterHandle::detached("info");
+        let err = filter.set("foo=bar=baz", None).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidLogFilter);
+        assert_eq!(filter.current(), normalized("info"));
+    }
+}
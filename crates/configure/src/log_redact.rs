@@ -0,0 +1,231 @@
+use std::{fmt, io};
+
+use serde_json::Value;
+use tracing::field::{Field, Visit};
+use tracing_subscriber::{
+    field::{MakeVisitor, VisitFmt, VisitOutput},
+    fmt::{format::Writer, MakeWriter},
+};
+
+use crate::redact::{Redactor, MASK};
+
+/// 文本格式输出的字段格式化器，敏感字段的值替换为占位符
+#[derive(Debug, Clone)]
+pub struct RedactFields<M> {
+    inner: M,
+    redactor: Redactor,
+}
+
+impl<M> RedactFields<M> {
+    pub fn new(inner: M, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<'a, M> MakeVisitor<Writer<'a>> for RedactFields<M>
+where
+    M: MakeVisitor<Writer<'a>>,
+{
+    type Visitor = RedactVisitor<M::Visitor>;
+
+    fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
+        RedactVisitor { inner: self.inner.make_visitor(target), redactor: self.redactor.clone() }
+    }
+}
+
+pub struct RedactVisitor<V> {
+    inner: V,
+    redactor: Redactor,
+}
+
+impl<V: Visit> RedactVisitor<V> {
+    /// 敏感字段返回 true 并记录占位符
+    fn masked(&mut self, field: &Field) -> bool {
+        let sensitive = self.redactor.is_sensitive_field(field.name());
+        if sensitive {
+            self.inner.record_str(field, MASK);
+        }
+        sensitive
+    }
+}
+
+impl<V: Visit> Visit for RedactVisitor<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if !self.masked(field) {
+            self.inner.record_f64(field, value)
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if !self.masked(field) {
+            self.inner.record_i64(field, value)
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if !self.masked(field) {
+            self.inner.record_u64(field, value)
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if !self.masked(field) {
+            self.inner.record_bool(field, value)
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if !self.masked(field) {
+            self.inner.record_str(field, value)
+        }
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        if !self.masked(field) {
+            self.inner.record_error(field, value)
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.masked(field) {
+            self.inner.record_debug(field, value)
+        }
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for RedactVisitor<V> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for RedactVisitor<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+/// JSON 格式输出(bunyan / json)的 writer，写出前把敏感字段的值替换为占位符
+pub struct RedactMakeWriter<M> {
+    inner: M,
+    redactor: Redactor,
+}
+
+impl<M> RedactMakeWriter<M> {
+    pub fn new(inner: M, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactMakeWriter<M> {
+    type Writer = RedactWriter<'a, M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactWriter { inner: self.inner.make_writer(), redactor: &self.redactor, buf: Vec::new() }
+    }
+}
+
+/// 每条日志对应一个 writer，drop 时统一处理缓冲的内容
+pub struct RedactWriter<'a, W: io::Write> {
+    inner: W,
+    redactor: &'a Redactor,
+    buf: Vec<u8>,
+}
+
+impl<W: io::Write> RedactWriter<'_, W> {
+    fn redact_line(&self, line: &[u8]) -> Vec<u8> {
+        match serde_json::from_slice::<Value>(line) {
+            Ok(mut value) => {
+                mask_json(&mut value, self.redactor);
+                serde_json::to_vec(&value).unwrap_or_else(|_| line.to_vec())
+            }
+            // 不是 JSON 的内容原样输出
+            Err(_) => line.to_vec(),
+        }
+    }
+}
+
+impl<W: io::Write> io::Write for RedactWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut out = Vec::with_capacity(self.buf.len());
+        for line in self.buf.split_inclusive(|b| *b == b'\n') {
+            let (content, newline) = match line.strip_suffix(b"\n") {
+                Some(content) => (content, true),
+                None => (line, false),
+            };
+            out.extend(self.redact_line(content));
+            if newline {
+                out.push(b'\n');
+            }
+        }
+        self.buf.clear();
+        self.inner.write_all(&out)?;
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> Drop for RedactWriter<'_, W> {
+    fn drop(&mut self) {
+        if !self.buf.is_empty() {
+            let _ = io::Write::flush(self);
+        }
+    }
+}
+
+fn mask_json(value: &mut Value, redactor: &Redactor) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if redactor.is_sensitive_field(key) {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    mask_json(value, redactor);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|v| mask_json(v, redactor)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn masks_nested_json_fields() {
+        let mut value = serde_json::json!({
+            "msg": "login",
+            "password": "hunter2",
+            "span": { "Authorization": "Bearer token", "path": "/login" },
+            "spans": [{ "cookie": "sid=1" }],
+        });
+        mask_json(&mut value, &Redactor::default());
+        assert_eq!(value["msg"], "login");
+        assert_eq!(value["password"], MASK);
+        assert_eq!(value["span"]["Authorization"], MASK);
+        assert_eq!(value["span"]["path"], "/login");
+        assert_eq!(value["spans"][0]["cookie"], MASK);
+    }
+
+    #[test]
+    fn writer_redacts_each_json_line_and_keeps_plain_text() {
+        let redactor = Redactor::default();
+        let mut out = Vec::new();
+        {
+            let mut writer = RedactWriter { inner: &mut out, redactor: &redactor, buf: Vec::new() };
+            writer.write_all(b"{\"password\":\"hunter2\"}\nplain password=hunter2\n").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"password\":\"***\"}\nplain password=hunter2\n"
+        );
+    }
+}
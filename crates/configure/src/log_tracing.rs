@@ -1,3 +1,12 @@
+use std::path::Path;
+
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{Sampler, SdkTracerProvider, Tracer},
+    Resource,
+};
 use tracing::{subscriber, Subscriber};
 use tracing_appender::{
     non_blocking::WorkerGuard,
@@ -6,62 +15,211 @@ use tracing_appender::{
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
 use tracing_subscriber::{
-    fmt::{self, MakeWriter},
-    layer::SubscriberExt,
-    EnvFilter, Registry,
+    fmt::{
+        self,
+        format::{DefaultFields, PrettyFields},
+        MakeWriter,
+    },
+    layer::{Layered, SubscriberExt},
+    reload, EnvFilter, Layer, Registry,
+};
+
+use crate::{
+    config_dir::base_dir,
+    log_config::{ConsoleFormat, FileConfig, FileFormat, FileRotation, TracingConfig},
+    log_filter::LogFilterHandle,
+    log_redact::{RedactFields, RedactMakeWriter},
+    otlp::OtlpConfig,
+    redact::Redactor,
+    AppConfig,
 };
 
-use crate::get_root_dir;
+/// 挂载了可替换过滤器的 subscriber，其余输出层都叠加在它之上
+type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+
+/// 日志系统的守卫，drop 时刷新文件日志并导出剩余的 span
+pub struct TracingGuard {
+    _worker_guard: Option<WorkerGuard>,
+    tracer_provider: Option<SdkTracerProvider>,
+    filter_handle: LogFilterHandle,
+}
+
+impl TracingGuard {
+    /// 运行时调整日志过滤规则的句柄
+    pub fn filter_handle(&self) -> LogFilterHandle {
+        self.filter_handle.clone()
+    }
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("failed to shutdown tracer provider: {e}");
+            }
+        }
+    }
+}
+
+pub fn init(config: &AppConfig) -> TracingGuard {
+    let tracing_config = &config.tracing;
 
-pub fn init() -> WorkerGuard {
-    let root_dir = get_root_dir().expect("Failed to get root dir");
-    let log_dir = root_dir.join("logs");
+    // 文件日志，目录、轮转策略和保留数量由配置决定
+    let (file_writer, worker_guard) = if tracing_config.file.enabled {
+        let appender =
+            file_appender(&tracing_config.file).expect("Failed to create log file appender");
+        let (non_blocking, guard) = tracing_appender::non_blocking(appender);
+        (Some(non_blocking), Some(guard))
+    } else {
+        (None, None)
+    };
 
-    // 创建按天轮转的文件日志记录器
-    let file_appender = RollingFileAppender::new(Rotation::DAILY, &log_dir, "app.log");
-    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+    // 优先使用 RUST_LOG 环境变量，否则使用配置中的级别和规则
+    let env_filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(tracing_config.filter_directive()))
+        .unwrap_or_else(|e| {
+            eprintln!("invalid log filter in [tracing], falling back to info: {e}");
+            EnvFilter::new("info")
+        });
+    // 过滤器可在运行时替换
+    let base_directive = env_filter.to_string();
+    let (env_filter, reload_handle) = reload::Layer::new(env_filter);
 
-    // 从环境变量获取日志级别，默认为info
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    // 始终创建 tracer provider 以便解析和传递 traceparent，只有开启 OTLP 时才导出 span
+    let tracer_provider = init_tracer_provider(&config.otlp, &config.profile.to_string());
+    let tracer = tracer_provider.tracer(config.otlp.service_name.clone());
 
-    let subscriber = create_subscriber("app", env_filter, non_blocking);
+    let subscriber = create_subscriber("app", tracing_config, env_filter, file_writer, tracer);
     init_subscriber(subscriber).expect("Failed to initialize subscriber");
 
-    guard
+    TracingGuard {
+        _worker_guard: worker_guard,
+        tracer_provider: Some(tracer_provider),
+        filter_handle: LogFilterHandle::new(reload_handle, base_directive),
+    }
+}
+
+fn file_appender(file: &FileConfig) -> anyhow::Result<RollingFileAppender> {
+    let dir = Path::new(&file.dir);
+    let log_dir = if dir.is_absolute() { dir.to_path_buf() } else { base_dir().join(dir) };
+    // 清理旧文件时目录需要已存在
+    std::fs::create_dir_all(&log_dir)?;
+    let rotation = match file.rotation {
+        FileRotation::Minutely => Rotation::MINUTELY,
+        FileRotation::Hourly => Rotation::HOURLY,
+        FileRotation::Daily => Rotation::DAILY,
+        FileRotation::Never => Rotation::NEVER,
+    };
+    let mut builder =
+        RollingFileAppender::builder().rotation(rotation).filename_prefix(&file.prefix);
+    if let Some(max_files) = file.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+    Ok(builder.build(log_dir)?)
+}
+
+/// 创建 tracer provider，开启 OTLP 时通过 gRPC 导出，导出器创建失败不影响服务启动
+fn init_tracer_provider(otlp: &OtlpConfig, profile: &str) -> SdkTracerProvider {
+    let attributes = otlp
+        .resource_attributes
+        .iter()
+        .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+        .chain([KeyValue::new("deployment.environment", profile.to_string())]);
+    let resource = Resource::builder()
+        .with_service_name(otlp.service_name.clone())
+        .with_attributes(attributes)
+        .build();
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(otlp.sampling_ratio)));
+
+    let mut builder = SdkTracerProvider::builder().with_sampler(sampler).with_resource(resource);
+    if otlp.enabled {
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(otlp.endpoint.clone())
+            .build();
+        match exporter {
+            Ok(exporter) => builder = builder.with_batch_exporter(exporter),
+            Err(e) => eprintln!("failed to initialize otlp exporter: {e}"),
+        }
+    }
+    let provider = builder.build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    // 使用 W3C Trace Context (traceparent / tracestate) 传递链路上下文
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    provider
 }
 
 fn create_subscriber<W>(
     name: &str,
-    env_filter: EnvFilter,
-    writer: W,
+    config: &TracingConfig,
+    env_filter: reload::Layer<EnvFilter, Registry>,
+    file_writer: Option<W>,
+    tracer: Tracer,
 ) -> impl Subscriber + Sync + Send
 where
     W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
 {
+    let mut layers: Vec<Box<dyn Layer<FilteredRegistry> + Send + Sync>> = Vec::new();
+    let redactor = Redactor::new(&config.redact);
+
     // 控制台输出层
-    let fmt_layer = fmt::Layer::default()
-        .with_level(true)
-        .with_timer(fmt::time::ChronoLocal::rfc_3339())
-        .with_span_events(fmt::format::FmtSpan::CLOSE)
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_line_number(true)
-        .with_ansi(true)
-        .compact();
-
-    // 文件输出层(JSON格式)
-    let file_layer = BunyanFormattingLayer::new(name.into(), writer);
-
-    //控制台输出层(JSON格式)
-    // let console_layer = BunyanFormattingLayer::new(name.into(), std::io::stdout);
-
-    Registry::default()
-        .with(env_filter)
-        .with(JsonStorageLayer)
-        // .with(console_layer)
-        .with(file_layer)
-        .with(fmt_layer)
+    if config.console.enabled {
+        let fmt_layer = fmt::Layer::default()
+            .with_level(true)
+            .with_timer(fmt::time::ChronoLocal::rfc_3339())
+            .with_span_events(fmt::format::FmtSpan::CLOSE)
+            .with_target(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_line_number(true)
+            .with_ansi(config.console.ansi);
+        // 文本格式在格式化字段时脱敏，JSON 格式在写出时脱敏
+        layers.push(match config.console.format {
+            ConsoleFormat::Pretty => fmt_layer
+                .pretty()
+                .fmt_fields(RedactFields::new(PrettyFields::new(), redactor.clone()))
+                .boxed(),
+            ConsoleFormat::Compact => fmt_layer
+                .compact()
+                .fmt_fields(RedactFields::new(DefaultFields::new(), redactor.clone()))
+                .boxed(),
+            ConsoleFormat::Json => fmt_layer
+                .json()
+                .with_writer(RedactMakeWriter::new(std::io::stdout, redactor.clone()))
+                .boxed(),
+        });
+    }
+
+    // 文件输出层
+    if let Some(writer) = file_writer {
+        match config.file.format {
+            FileFormat::Bunyan => {
+                layers.push(JsonStorageLayer.boxed());
+                let writer = RedactMakeWriter::new(writer, redactor);
+                layers.push(BunyanFormattingLayer::new(name.into(), writer).boxed());
+            }
+            FileFormat::Json => layers.push(
+                fmt::layer()
+                    .json()
+                    .with_writer(RedactMakeWriter::new(writer, redactor))
+                    .with_ansi(false)
+                    .boxed(),
+            ),
+            FileFormat::Text => layers.push(
+                fmt::layer()
+                    .fmt_fields(RedactFields::new(DefaultFields::new(), redactor))
+                    .with_writer(writer)
+                    .with_ansi(false)
+                    .boxed(),
+            ),
+        }
+    }
+
+    // 链路追踪层，负责生成 trace id 和导出 span
+    layers.push(tracing_opentelemetry::layer().with_tracer(tracer).boxed());
+
+    Registry::default().with(env_filter).with(layers)
 }
 
 pub fn init_subscriber<S>(subscriber: S) -> anyhow::Result<()>
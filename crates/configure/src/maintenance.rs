@@ -0,0 +1,119 @@
+use std::sync::{Arc, RwLock};
+
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode};
+
+/// 维护模式下仍然放行的路径，包括它们的子路径
+const EXEMPT_PREFIXES: [&str; 3] = ["/health", "/admin", "/metrics"];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceMode {
+    /// 正常服务
+    #[default]
+    Off,
+    /// 只读模式，拒绝修改类请求
+    ReadOnly,
+    /// 维护模式，拒绝所有业务请求
+    Maintenance,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    pub mode: MaintenanceMode,
+    /// 返回给客户端的提示信息
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// 运行时可切换的维护模式开关
+#[derive(Debug, Default, Clone)]
+pub struct Maintenance {
+    status: Arc<RwLock<MaintenanceStatus>>,
+}
+
+impl Maintenance {
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    pub fn set(&self, status: MaintenanceStatus) {
+        *self.status.write().unwrap() = status;
+    }
+
+    /// 判断请求是否应被拒绝，拒绝时返回对应的错误
+    pub fn rejection(&self, method: &Method, path: &str) -> Option<AppError> {
+        if is_exempt(path) {
+            return None;
+        }
+        let status = self.status.read().unwrap();
+        let rejected = match status.mode {
+            MaintenanceMode::Off => false,
+            MaintenanceMode::ReadOnly => !is_read_only_method(method),
+            MaintenanceMode::Maintenance => true,
+        };
+        if !rejected {
+            return None;
+        }
+        let (code, default_message) = match status.mode {
+            MaintenanceMode::ReadOnly => (ErrorCode::ReadOnlyMode, "Service is in read-only mode"),
+            _ => (ErrorCode::MaintenanceMode, "Service is under maintenance"),
+        };
+        let message = status.message.clone().unwrap_or_else(|| default_message.to_string());
+        Some(AppError::coded(code, message))
+    }
+}
+
+fn is_exempt(path: &str) -> bool {
+    matches_any_prefix(path, &EXEMPT_PREFIXES)
+}
+
+/// 按路径段匹配，`/admin` 匹配 `/admin` 和 `/admin/...`，不匹配 `/administrator`
+pub fn matches_any_prefix(path: &str, prefixes: &[&str]) -> bool {
+    prefixes.iter().any(|prefix| {
+        path.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+fn is_read_only_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maintenance(mode: MaintenanceMode) -> Maintenance {
+        let maintenance = Maintenance::default();
+        maintenance.set(MaintenanceStatus { mode, message: None });
+        maintenance
+    }
+
+    #[test]
+    fn exempts_whole_path_segments_only() {
+        let maintenance = maintenance(MaintenanceMode::Maintenance);
+        for path in ["/health", "/health/ready", "/admin", "/admin/maintenance", "/metrics"] {
+            assert!(maintenance.rejection(&Method::POST, path).is_none(), "{path}");
+        }
+        for path in ["/administrator", "/healthz", "/health-check", "/metricsfoo", "/users"] {
+            assert!(maintenance.rejection(&Method::GET, path).is_some(), "{path}");
+        }
+    }
+
+    #[test]
+    fn read_only_rejects_writes() {
+        let maintenance = maintenance(MaintenanceMode::ReadOnly);
+        assert!(maintenance.rejection(&Method::GET, "/users").is_none());
+        assert!(maintenance.rejection(&Method::HEAD, "/users").is_none());
+        let err = maintenance.rejection(&Method::POST, "/users").unwrap();
+        assert_eq!(err.code(), ErrorCode::ReadOnlyMode);
+    }
+
+    #[test]
+    fn off_allows_everything() {
+        let maintenance = maintenance(MaintenanceMode::Off);
+        assert!(maintenance.rejection(&Method::DELETE, "/users/1").is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 指标配置
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// 是否启用指标采集
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 单独的管理端口，配置后 `/metrics` 只在该端口暴露，否则挂在业务端口上
+    #[serde(default)]
+    pub admin_port: Option<u16>,
+    /// 是否同时通过 OTLP 导出指标，collector 地址使用 `[otlp]` 中的配置
+    #[serde(default)]
+    pub otlp_export: bool,
+    /// OTLP 指标导出间隔(秒)
+    #[serde(default = "default_otlp_export_interval_secs")]
+    pub otlp_export_interval_secs: u64,
+    /// 数据库连接池指标的采样间隔(秒)
+    #[serde(default = "default_pool_sample_interval_secs")]
+    pub pool_sample_interval_secs: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            admin_port: None,
+            otlp_export: false,
+            otlp_export_interval_secs: default_otlp_export_interval_secs(),
+            pool_sample_interval_secs: default_pool_sample_interval_secs(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_otlp_export_interval_secs() -> u64 {
+    30
+}
+
+fn default_pool_sample_interval_secs() -> u64 {
+    15
+}
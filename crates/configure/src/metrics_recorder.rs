@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use ::metrics::{
+    describe_counter, describe_gauge, describe_histogram, Counter, CounterFn, Gauge, GaugeFn,
+    Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use metrics_util::layers::FanoutBuilder;
+use once_cell::sync::OnceCell;
+use opentelemetry::{
+    metrics::{Meter, MeterProvider as _},
+    InstrumentationScope, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+
+use crate::AppConfig;
+
+/// 耗时类指标(`*_seconds`)的直方图分桶
+const LATENCY_BUCKETS: [f64; 12] =
+    [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+static PROMETHEUS: OnceCell<PrometheusHandle> = OnceCell::new();
+
+/// 指标系统的守卫，drop 时导出剩余的 OTLP 指标
+pub struct MetricsGuard {
+    meter_provider: Option<SdkMeterProvider>,
+}
+
+impl Drop for MetricsGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.meter_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("failed to shutdown otlp meter provider: {e}");
+            }
+        }
+    }
+}
+
+/// 安装全局指标 recorder：Prometheus 文本格式，可选同时通过 OTLP 导出
+pub fn init(config: &AppConfig) -> MetricsGuard {
+    if !config.metrics.enabled {
+        return MetricsGuard { meter_provider: None };
+    }
+
+    let prometheus = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), &LATENCY_BUCKETS)
+        .expect("invalid histogram buckets")
+        .build_recorder();
+    let handle = prometheus.handle();
+    let mut fanout = FanoutBuilder::default().add_recorder(prometheus);
+
+    let meter_provider = if config.metrics.otlp_export {
+        init_meter_provider(config)
+            .map_err(|e| eprintln!("failed to initialize otlp metrics exporter: {e}"))
+            .ok()
+    } else {
+        None
+    };
+    if let Some(provider) = &meter_provider {
+        let scope = InstrumentationScope::builder(config.otlp.service_name.clone()).build();
+        let meter = provider.meter_with_scope(scope);
+        fanout = fanout.add_recorder(OtelRecorder::new(meter));
+    }
+
+    ::metrics::set_global_recorder(fanout.build()).expect("Failed to install metrics recorder");
+    describe_metrics();
+
+    // 直方图数据需要定期整理，否则会一直累积
+    let upkeep = handle.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            ticker.tick().await;
+            upkeep.run_upkeep();
+        }
+    });
+    let _ = PROMETHEUS.set(handle);
+
+    MetricsGuard { meter_provider }
+}
+
+/// 以 Prometheus 文本格式输出当前指标
+pub fn render() -> String {
+    PROMETHEUS.get().map(|handle| handle.render()).unwrap_or_default()
+}
+
+fn init_meter_provider(config: &AppConfig) -> anyhow::Result<SdkMeterProvider> {
+    let exporter = opentelemetry_otlp::MetricExporter::builder()
+        .with_tonic()
+        .with_endpoint(config.otlp.endpoint.clone())
+        .build()?;
+    let reader = PeriodicReader::builder(exporter)
+        .with_interval(Duration::from_secs(config.metrics.otlp_export_interval_secs))
+        .build();
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name(config.otlp.service_name.clone())
+        .build();
+    Ok(SdkMeterProvider::builder().with_reader(reader).with_resource(resource).build())
+}
+
+fn describe_metrics() {
+    describe_counter!("http_requests_total", "Total number of HTTP requests");
+    describe_histogram!(
+        "http_request_duration_seconds",
+        Unit::Seconds,
+        "HTTP request latency by route and status"
+    );
+    describe_gauge!("http_requests_in_flight", "Number of HTTP requests being processed");
+    describe_counter!("http_requests_shed_total", "Requests rejected by load shedding");
+    describe_counter!("http_panics_total", "Request handlers that panicked");
+    describe_counter!("jwt_rejections_total", "Requests rejected by JWT authentication");
+    describe_counter!("config_reloads_total", "Config reloads by result");
+    describe_gauge!(
+        "config_last_reload_success_timestamp_seconds",
+        "Unix time of the last successful config reload"
+    );
+    describe_gauge!("db_pool_connections", "Database pool connections by state");
+    describe_gauge!("db_pool_max_connections", "Configured maximum database pool size");
+    describe_histogram!(
+        "db_pool_probe_acquire_seconds",
+        Unit::Seconds,
+        "Time for the periodic pool probe to acquire a connection, not request wait time"
+    );
+    describe_counter!(
+        "db_pool_probe_errors_total",
+        "Periodic pool probes that failed to acquire a connection"
+    );
+}
+
+/// 把 `metrics` 的指标转发到 OpenTelemetry instrument
+struct OtelRecorder {
+    meter: Meter,
+    counters: Mutex<HashMap<Key, Arc<OtelCounter>>>,
+    gauges: Mutex<HashMap<Key, Arc<OtelGauge>>>,
+    histograms: Mutex<HashMap<Key, Arc<OtelHistogram>>>,
+}
+
+impl OtelRecorder {
+    fn new(meter: Meter) -> Self {
+        Self {
+            meter,
+            counters: Mutex::default(),
+            gauges: Mutex::default(),
+            histograms: Mutex::default(),
+        }
+    }
+}
+
+fn attributes(key: &Key) -> Vec<KeyValue> {
+    key.labels().map(|l| KeyValue::new(l.key().to_string(), l.value().to_string())).collect()
+}
+
+impl Recorder for OtelRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters.entry(key.clone()).or_insert_with(|| {
+            Arc::new(OtelCounter {
+                counter: self.meter.u64_counter(key.name().to_string()).build(),
+                attributes: attributes(key),
+            })
+        });
+        Counter::from_arc(counter.clone())
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        let mut gauges = self.gauges.lock().unwrap();
+        let gauge = gauges.entry(key.clone()).or_insert_with(|| {
+            Arc::new(OtelGauge {
+                gauge: self.meter.f64_gauge(key.name().to_string()).build(),
+                attributes: attributes(key),
+                value: AtomicU64::new(0f64.to_bits()),
+            })
+        });
+        Gauge::from_arc(gauge.clone())
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = histograms.entry(key.clone()).or_insert_with(|| {
+            Arc::new(OtelHistogram {
+                histogram: self.meter.f64_histogram(key.name().to_string()).build(),
+                attributes: attributes(key),
+            })
+        });
+        Histogram::from_arc(histogram.clone())
+    }
+}
+
+struct OtelCounter {
+    counter: opentelemetry::metrics::Counter<u64>,
+    attributes: Vec<KeyValue>,
+}
+
+impl CounterFn for OtelCounter {
+    fn increment(&self, value: u64) {
+        self.counter.add(value, &self.attributes);
+    }
+
+    fn absolute(&self, _value: u64) {}
+}
+
+struct OtelGauge {
+    gauge: opentelemetry::metrics::Gauge<f64>,
+    attributes: Vec<KeyValue>,
+    /// 当前值(f64 的二进制表示)，用于支持 increment/decrement
+    value: AtomicU64,
+}
+
+impl OtelGauge {
+    fn update(&self, f: impl Fn(f64) -> f64) {
+        let mut current = self.value.load(Ordering::Relaxed);
+        loop {
+            let new = f(f64::from_bits(current));
+            match self.value.compare_exchange_weak(
+                current,
+                new.to_bits(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    self.gauge.record(new, &self.attributes);
+                    return;
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl GaugeFn for OtelGauge {
+    fn increment(&self, value: f64) {
+        self.update(|v| v + value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.update(|v| v - value);
+    }
+
+    fn set(&self, value: f64) {
+        self.update(|_| value);
+    }
+}
+
+struct OtelHistogram {
+    histogram: opentelemetry::metrics::Histogram<f64>,
+    attributes: Vec<KeyValue>,
+}
+
+impl HistogramFn for OtelHistogram {
+    fn record(&self, value: f64) {
+        self.histogram.record(value, &self.attributes);
+    }
+}
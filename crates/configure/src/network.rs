@@ -0,0 +1,59 @@
+use std::{collections::HashMap, net::IpAddr};
+
+use ipnet::IpNet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// 受信任的代理网段，只有来自这些地址的请求才会解析 `forwarded_header` 指定的转发头
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub trusted_proxies: Vec<IpNet>,
+    /// 受信任代理设置的转发头，只解析这一个，客户端自行添加的另一个转发头会被忽略
+    #[serde(default)]
+    pub forwarded_header: ForwardedHeader,
+    /// 按路由分组配置的 IP 访问控制，例如 `admin`
+    #[serde(default)]
+    pub access: HashMap<String, IpAccessConfig>,
+}
+
+/// 代理传递客户端地址使用的请求头
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ForwardedHeader {
+    /// `X-Forwarded-For: client, proxy1`
+    #[default]
+    XForwardedFor,
+    /// RFC 7239 `Forwarded: for=client, for=proxy1`
+    Forwarded,
+}
+
+impl NetworkConfig {
+    pub fn is_trusted_proxy(&self, ip: &IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(ip))
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct IpAccessConfig {
+    /// 允许访问的网段，为空时允许所有地址，业务端口上的 `/metrics` 则拒绝所有地址
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub allow: Vec<IpNet>,
+    /// 拒绝访问的网段，优先于 allow
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub deny: Vec<IpNet>,
+}
+
+impl IpAccessConfig {
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        if self.deny.iter().any(|net| net.contains(ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip))
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// OpenTelemetry OTLP 导出配置
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct OtlpConfig {
+    /// 是否启用 OTLP 导出
+    #[serde(default)]
+    pub enabled: bool,
+    /// collector 的 gRPC 地址
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+    /// 上报的 `service.name`
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// 采样比例，取值 0.0 ~ 1.0，上游已采样的请求始终跟随上游决定
+    #[serde(default = "default_sampling_ratio")]
+    pub sampling_ratio: f64,
+    /// 额外的 resource 属性，例如 `service.version`
+    #[serde(default)]
+    pub resource_attributes: HashMap<String, String>,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: default_endpoint(),
+            service_name: default_service_name(),
+            sampling_ratio: default_sampling_ratio(),
+            resource_attributes: HashMap::new(),
+        }
+    }
+}
+
+fn default_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_service_name() -> String {
+    "axum-sqlx".to_string()
+}
+
+fn default_sampling_ratio() -> f64 {
+    1.0
+}
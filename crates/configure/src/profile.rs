@@ -1,29 +1,145 @@
-use serde::Deserialize;
-
-#[derive(
-    Debug,
-    Default,
-    strum::Display,
-    strum::EnumString,
-    Deserialize,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Clone,
-    Copy,
-)]
-pub enum Profile {
-    #[serde(rename = "test")]
-    #[strum(serialize = "test")]
-    Test,
-
-    #[default]
-    #[serde(rename = "development")]
-    #[strum(serialize = "development")]
-    Development,
-
-    #[serde(rename = "production")]
-    #[strum(serialize = "production")]
-    Production,
+use std::{borrow::Cow, fmt};
+
+use config::ConfigError;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize};
+
+/// 运行环境，对应配置目录中的 `{name}.toml`
+///
+/// 内置 development / test / production，也可以是任意名称，例如 `staging`，
+/// 配置文件中的 `extends` 指定继承的 profile
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub struct Profile {
+    name: String,
+    /// 通过 `extends` 继承的 profile，从近到远
+    ancestors: Vec<String>,
+}
+
+impl Profile {
+    pub const DEVELOPMENT: &'static str = "development";
+    pub const TEST: &'static str = "test";
+    pub const PRODUCTION: &'static str = "production";
+
+    /// 不是 profile 的配置文件名
+    const RESERVED: [&'static str; 2] = ["default", "local"];
+
+    pub fn new(name: &str) -> Result<Self, ConfigError> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            return Err(ConfigError::Message(format!(
+                "invalid profile `{name}`, only lowercase letters, digits, `-` and `_` are allowed"
+            )));
+        }
+        if Self::RESERVED.contains(&name) {
+            return Err(ConfigError::Message(format!("`{name}` is not a profile name")));
+        }
+        Ok(Self { name: name.to_string(), ancestors: Vec::new() })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 通过 `extends` 继承的 profile，从近到远
+    pub fn ancestors(&self) -> &[String] {
+        &self.ancestors
+    }
+
+    pub(crate) fn with_ancestors(mut self, ancestors: Vec<String>) -> Self {
+        self.ancestors = ancestors;
+        self
+    }
+
+    /// 是指定的 profile 或继承自它，例如继承 production 的 staging 也按生产环境处理
+    pub fn is(&self, name: &str) -> bool {
+        self.name == name || self.ancestors.iter().any(|ancestor| ancestor == name)
+    }
+
+    pub fn is_development(&self) -> bool {
+        self.is(Self::DEVELOPMENT)
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.is(Self::PRODUCTION)
+    }
+
+    /// 是否把内部错误的细节返回给客户端，只有开发和测试环境返回，新增的 profile 默认不返回
+    pub fn exposes_internal_errors(&self) -> bool {
+        self.is_development() || self.is(Self::TEST)
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self { name: Self::DEVELOPMENT.to_string(), ancestors: Vec::new() }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl From<String> for Profile {
+    fn from(name: String) -> Self {
+        Self { name, ancestors: Vec::new() }
+    }
+}
+
+impl From<Profile> for String {
+    fn from(profile: Profile) -> Self {
+        profile.name
+    }
+}
+
+impl JsonSchema for Profile {
+    fn schema_name() -> Cow<'static, str> {
+        "Profile".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string", "pattern": "^[a-z0-9_-]+$" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, ancestors: &[&str]) -> Profile {
+        Profile::new(name)
+            .unwrap()
+            .with_ancestors(ancestors.iter().map(|a| a.to_string()).collect())
+    }
+
+    #[test]
+    fn rejects_invalid_and_reserved_names() {
+        assert!(Profile::new("staging-eu_1").is_ok());
+        assert!(Profile::new("Staging").is_err());
+        assert!(Profile::new("").is_err());
+        assert!(Profile::new("local").is_err());
+        assert!(Profile::new("default").is_err());
+    }
+
+    #[test]
+    fn checks_follow_inheritance() {
+        let staging = profile("staging", &[Profile::PRODUCTION]);
+        assert!(staging.is_production());
+        assert!(!staging.is_development());
+    }
+
+    #[test]
+    fn only_development_and_test_expose_internal_errors() {
+        assert!(profile(Profile::DEVELOPMENT, &[]).exposes_internal_errors());
+        assert!(profile(Profile::TEST, &[]).exposes_internal_errors());
+        assert!(profile("local-dev", &[Profile::DEVELOPMENT]).exposes_internal_errors());
+        assert!(!profile(Profile::PRODUCTION, &[]).exposes_internal_errors());
+        assert!(!profile("staging", &[Profile::PRODUCTION]).exposes_internal_errors());
+        assert!(!profile("qa", &[]).exposes_internal_errors());
+    }
 }
@@ -0,0 +1,144 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::http::{HeaderMap, HeaderValue, Uri};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 日志中替换敏感值使用的占位符
+pub const MASK: &str = "***";
+
+/// 日志脱敏配置，名称不区分大小写
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct RedactConfig {
+    /// 需要脱敏的请求头
+    #[serde(default = "default_names")]
+    pub headers: Vec<String>,
+    /// 需要脱敏的查询参数
+    #[serde(default = "default_names")]
+    pub query_params: Vec<String>,
+    /// 需要脱敏的 span / 事件字段
+    #[serde(default = "default_names")]
+    pub fields: Vec<String>,
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        Self { headers: default_names(), query_params: default_names(), fields: default_names() }
+    }
+}
+
+fn default_names() -> Vec<String> {
+    ["authorization", "cookie", "x-api-key", "password"].map(String::from).to_vec()
+}
+
+/// 根据 [`RedactConfig`] 判断并替换敏感值
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    headers: Arc<HashSet<String>>,
+    query_params: Arc<HashSet<String>>,
+    fields: Arc<HashSet<String>>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactConfig) -> Self {
+        let lower = |names: &[String]| Arc::new(names.iter().map(|n| n.to_lowercase()).collect());
+        Self {
+            headers: lower(&config.headers),
+            query_params: lower(&config.query_params),
+            fields: lower(&config.fields),
+        }
+    }
+
+    pub fn is_sensitive_header(&self, name: &str) -> bool {
+        self.headers.contains(&name.to_lowercase())
+    }
+
+    pub fn is_sensitive_query_param(&self, name: &str) -> bool {
+        self.query_params.contains(&name.to_lowercase())
+    }
+
+    pub fn is_sensitive_field(&self, name: &str) -> bool {
+        self.fields.contains(&name.to_lowercase())
+    }
+
+    /// 复制请求头并替换敏感头的值
+    pub fn headers(&self, headers: &HeaderMap) -> HeaderMap {
+        let mut headers = headers.clone();
+        let sensitive: Vec<_> = headers
+            .keys()
+            .filter(|name| self.is_sensitive_header(name.as_str()))
+            .cloned()
+            .collect();
+        for name in sensitive {
+            headers.insert(name, HeaderValue::from_static(MASK));
+        }
+        headers
+    }
+
+    /// 替换 uri 中敏感查询参数的值
+    pub fn uri(&self, uri: &Uri) -> String {
+        let Some(query) = uri.query() else {
+            return uri.to_string();
+        };
+        let query = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, _)) if self.is_sensitive_query_param(key) => format!("{key}={MASK}"),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        format!("{}?{}", uri.path(), query)
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(&RedactConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_sensitive_headers_case_insensitively() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_static("Bearer token"));
+        headers.insert("X-API-KEY", HeaderValue::from_static("secret"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+
+        let redacted = Redactor::default().headers(&headers);
+        assert_eq!(redacted["authorization"], MASK);
+        assert_eq!(redacted["x-api-key"], MASK);
+        assert_eq!(redacted["accept"], "application/json");
+        // 原请求头不受影响
+        assert_eq!(headers["authorization"], "Bearer token");
+    }
+
+    #[test]
+    fn masks_sensitive_query_params() {
+        let redactor = Redactor::default();
+        let uri: Uri = "/login?user=alice&Password=hunter2&flag".parse().unwrap();
+        assert_eq!(redactor.uri(&uri), "/login?user=alice&Password=***&flag");
+
+        let uri: Uri = "/users?page=2".parse().unwrap();
+        assert_eq!(redactor.uri(&uri), "/users?page=2");
+    }
+
+    #[test]
+    fn uses_configured_names() {
+        let config = RedactConfig {
+            headers: vec!["X-Session".to_string()],
+            query_params: vec![],
+            fields: vec!["Token".to_string()],
+        };
+        let redactor = Redactor::new(&config);
+        assert!(redactor.is_sensitive_header("x-session"));
+        assert!(!redactor.is_sensitive_header("authorization"));
+        assert!(!redactor.is_sensitive_query_param("password"));
+        assert!(redactor.is_sensitive_field("token"));
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use arc_swap::{ArcSwap, Guard};
+use config::ConfigError;
+use metrics::{counter, gauge};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::{config_dir::ConfigDir, log_filter::LogFilterHandle, AppConfig, LoadOptions};
+
+/// 文件变化后等待该时间再重新加载，合并编辑器保存时产生的多次事件
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 运行时可替换的配置，请求路径上的中间件每次请求读取当前值
+#[derive(Clone)]
+pub struct LiveConfig(Arc<ArcSwap<AppConfig>>);
+
+impl LiveConfig {
+    pub fn new(config: AppConfig) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(config)))
+    }
+
+    /// 当前配置，适合在请求处理中短暂持有
+    pub fn load(&self) -> Guard<Arc<AppConfig>> {
+        self.0.load()
+    }
+
+    /// 当前配置，适合长期持有
+    pub fn load_full(&self) -> Arc<AppConfig> {
+        self.0.load_full()
+    }
+}
+
+/// 一次重新加载的结果，只包含配置项路径，不包含配置值
+#[derive(Debug, Default)]
+pub struct ReloadOutcome {
+    /// 已经生效的配置项
+    pub applied: Vec<String>,
+    /// 修改了但需要重启才能生效的配置项
+    pub ignored: Vec<String>,
+}
+
+/// 重新读取配置目录，校验通过后替换可热更新的配置项
+#[derive(Clone)]
+pub struct ConfigReloader {
+    live: LiveConfig,
+    log_filter: LogFilterHandle,
+    /// 与启动时读取配置使用相同的选项
+    options: LoadOptions,
+}
+
+impl ConfigReloader {
+    pub fn new(live: LiveConfig, log_filter: LogFilterHandle, options: LoadOptions) -> Self {
+        Self { live, log_filter, options }
+    }
+
+    /// 重新加载配置，新配置存在错误时保留当前配置并返回错误
+    pub fn reload(&self) -> Result<ReloadOutcome, ConfigError> {
+        let (new, report) = AppConfig::load_unchecked(&self.options)?;
+        if report.has_errors() {
+            return Err(ConfigError::Message(report.to_string()));
+        }
+        for problem in report.warnings() {
+            warn!("config {}", problem);
+        }
+
+        let current = self.live.load_full();
+        let merged = current.with_reloadable(&new);
+        let outcome = ReloadOutcome {
+            applied: changed_paths(&to_value(&current), &to_value(&merged)),
+            ignored: changed_paths(&to_value(&merged), &to_value(&new)),
+        };
+        let directive = merged.tracing.filter_directive();
+        if directive != current.tracing.filter_directive() {
+            self.log_filter
+                .set(&directive, None)
+                .map_err(|e| ConfigError::Message(e.to_string()))?;
+        }
+        self.live.0.store(Arc::new(merged));
+        Ok(outcome)
+    }
+
+    /// 重新加载并记录日志和指标
+    fn reload_logged(&self, trigger: &str) {
+        match self.reload() {
+            Ok(outcome) => {
+                counter!("config_reloads_total", "result" => "success").increment(1);
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                gauge!("config_last_reload_success_timestamp_seconds").set(now.as_secs_f64());
+                for path in &outcome.ignored {
+                    warn!(trigger, "config `{}` changed but requires a restart, ignored", path);
+                }
+                if outcome.applied.is_empty() {
+                    info!(trigger, "config reloaded, nothing changed");
+                } else {
+                    info!(trigger, changed = ?outcome.applied, "config reloaded");
+                }
+            }
+            Err(e) => {
+                counter!("config_reloads_total", "result" => "failure").increment(1);
+                error!(trigger, "config reload failed, keeping current config: {}", e);
+            }
+        }
+    }
+
+    /// 后台监听 SIGHUP 和配置目录的文件变化，收到后重新加载配置
+    ///
+    /// 使用内置配置或关闭了 `server.watch_config` 时只监听 SIGHUP
+    pub fn spawn(self) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = match ConfigDir::discover(self.options.config_dir.as_deref()) {
+            Ok(ConfigDir::Dir(dir)) if self.live.load().server.watch_config => {
+                match watch_dir(&dir, tx) {
+                    Ok(watcher) => {
+                        info!("watching config dir {}", dir.display());
+                        Some(watcher)
+                    }
+                    Err(e) => {
+                        warn!("failed to watch config dir {}: {}", dir.display(), e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        tokio::spawn(async move {
+            // watcher 被 drop 后停止监听
+            let _watcher = watcher;
+            #[cfg(unix)]
+            let mut hangup =
+                match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                    Ok(signal) => Some(signal),
+                    Err(e) => {
+                        warn!("failed to install SIGHUP handler: {}", e);
+                        None
+                    }
+                };
+            loop {
+                #[cfg(unix)]
+                let sighup = async {
+                    match hangup.as_mut() {
+                        Some(signal) => signal.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let sighup = std::future::pending::<Option<()>>();
+
+                let trigger = tokio::select! {
+                    Some(()) = sighup => "sighup",
+                    Some(()) = rx.recv() => {
+                        tokio::time::sleep(DEBOUNCE).await;
+                        while rx.try_recv().is_ok() {}
+                        "file"
+                    }
+                    else => break,
+                };
+                self.reload_logged(trigger);
+            }
+        });
+    }
+}
+
+fn watch_dir(
+    dir: &std::path::Path,
+    tx: mpsc::UnboundedSender<()>,
+) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if !matches!(event.kind, EventKind::Access(_)) {
+                let _ = tx.send(());
+            }
+        }
+    })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+impl AppConfig {
+    /// 以当前配置为基础，取新配置中可以热更新的配置项
+    fn with_reloadable(&self, new: &AppConfig) -> AppConfig {
+        let mut merged = self.clone();
+        merged.server.max_in_flight = new.server.max_in_flight;
+        merged.server.retry_after_secs = new.server.retry_after_secs;
+        merged.server.readiness_timeout_ms = new.server.readiness_timeout_ms;
+        merged.server.shutdown_delay_secs = new.server.shutdown_delay_secs;
+        merged.server.cors = new.server.cors.clone();
+        merged.network = new.network.clone();
+        merged.features = new.features.clone();
+        // 设置了 RUST_LOG 时日志过滤规则以环境变量为准
+        if std::env::var_os("RUST_LOG").is_none() {
+            merged.tracing.log_level = new.tracing.log_level.clone();
+            merged.tracing.directives = new.tracing.directives.clone();
+        }
+        merged
+    }
+}
+
+fn to_value(config: &AppConfig) -> Value {
+    serde_json::to_value(config).unwrap_or_default()
+}
+
+/// 两份配置中取值不同的配置项路径
+fn changed_paths(old: &Value, new: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_changes("", old, new, &mut paths);
+    paths
+}
+
+fn collect_changes(prefix: &str, old: &Value, new: &Value, paths: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                let null = Value::Null;
+                collect_changes(
+                    &path,
+                    old.get(key).unwrap_or(&null),
+                    new.get(key).unwrap_or(&null),
+                    paths,
+                );
+            }
+        }
+        (old, new) if old != new => paths.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reports_changed_leaf_paths() {
+        let old = json!({ "server": { "port": 3000, "cors": { "allow_origins": ["*"] } }, "jwt": { "expired": 6 } });
+        let new = json!({ "server": { "port": 3001, "cors": { "allow_origins": ["https://a.com"] } }, "jwt": { "expired": 6 } });
+        assert_eq!(changed_paths(&old, &new), ["server.cors.allow_origins", "server.port"]);
+        assert!(changed_paths(&old, &old).is_empty());
+    }
+
+    /// 整个对象新增或删除时报告对象本身的路径
+    #[test]
+    fn reports_added_and_removed_keys() {
+        let old = json!({ "features": { "flags": { "a": { "enabled": true } } } });
+        let new = json!({ "features": { "flags": { "b": { "enabled": true } } } });
+        assert_eq!(changed_paths(&old, &new), ["features.flags.a", "features.flags.b"]);
+    }
+
+    #[test]
+    fn reports_type_changes_at_the_changed_path() {
+        let old = json!({ "metrics": { "admin_port": null } });
+        let new = json!({ "metrics": { "admin_port": 9100 } });
+        assert_eq!(changed_paths(&old, &new), ["metrics.admin_port"]);
+
+        let old = json!({ "otlp": { "resource_attributes": {} } });
+        let new = json!({ "otlp": { "resource_attributes": "none" } });
+        assert_eq!(changed_paths(&old, &new), ["otlp.resource_attributes"]);
+    }
+}
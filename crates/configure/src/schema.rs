@@ -0,0 +1,43 @@
+use serde_json::{json, Value};
+
+use crate::AppConfig;
+
+/// 开启严格模式的环境变量，值为 `1` 或 `true` 时未知的配置项视为错误
+pub const STRICT_ENV: &str = "APP_CONFIG_STRICT";
+
+/// 环境变量是否开启了严格模式，严格模式下未知的配置项是错误而不是警告
+pub fn strict_from_env() -> bool {
+    std::env::var(STRICT_ENV).is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+}
+
+/// 配置文件的 JSON Schema，供编辑器校验 `setting/*.toml`
+///
+/// 每个配置文件只是合并中的一层，所以不要求任何配置项必须出现；不允许未知的配置项
+pub fn config_schema() -> Value {
+    let mut schema = schemars::schema_for!(AppConfig).to_value();
+    remove_required(&mut schema);
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        properties.insert(
+            "extends".to_string(),
+            json!({
+                "description": "继承的 profile，先加载它的配置文件，再用当前文件覆盖",
+                "type": "string",
+            }),
+        );
+    }
+    schema
+}
+
+fn remove_required(schema: &mut Value) {
+    match schema {
+        Value::Object(map) => {
+            // 属性名也可能是 `required`，只删除必填项列表
+            if map.get("required").is_some_and(Value::is_array) {
+                map.remove("required");
+            }
+            map.values_mut().for_each(remove_required);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_required),
+        _ => {}
+    }
+}
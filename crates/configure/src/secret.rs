@@ -0,0 +1,135 @@
+use std::fmt;
+
+use config::{Config, ConfigError, Map, Source, Value, ValueKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 配置值中引用文件内容的前缀，例如 `password = "file:/run/secrets/db_password"`
+pub const FILE_REF_PREFIX: &str = "file:";
+
+/// 敏感配置值，`Debug` 不输出真实内容，需要时通过 [`Secret::expose`] 读取
+///
+/// 序列化时输出真实内容，打印配置时先用 [`crate::AppConfig::redacted`] 替换
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+/// 读取密钥文件，去掉末尾的换行
+pub fn read_secret_file(path: &str, key: &str) -> Result<String, ConfigError> {
+    std::fs::read_to_string(path)
+        .map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|e| ConfigError::Message(format!("failed to read `{path}` for `{key}`: {e}")))
+}
+
+/// 把配置中 `file:` 开头的值替换为对应文件的内容
+pub fn resolve_file_refs(config: Config) -> Result<Config, ConfigError> {
+    let mut refs = Vec::new();
+    collect_file_refs("", config.collect()?, &mut refs);
+    if refs.is_empty() {
+        return Ok(config);
+    }
+
+    let mut builder = Config::builder().add_source(config);
+    for (key, path) in refs {
+        let content = read_secret_file(&path, &key)?;
+        builder = builder.set_override(key, content)?;
+    }
+    builder.build()
+}
+
+fn collect_file_refs(prefix: &str, table: Map<String, Value>, refs: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key } else { format!("{prefix}.{key}") };
+        match value.kind {
+            ValueKind::String(s) => {
+                if let Some(path) = s.strip_prefix(FILE_REF_PREFIX) {
+                    refs.push((key, path.to_string()));
+                }
+            }
+            ValueKind::Table(table) => collect_file_refs(&key, table, refs),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+
+    fn temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!("secret-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn resolves_file_refs_in_nested_tables() {
+        let path = temp_file("jwt", "from-file\r\n");
+        let config = Config::builder()
+            .set_override("jwt.secret", format!("{FILE_REF_PREFIX}{path}"))
+            .unwrap()
+            .set_override("server.host", "127.0.0.1")
+            .unwrap()
+            .build()
+            .unwrap();
+        let config = resolve_file_refs(config).unwrap();
+        assert_eq!(config.get_string("jwt.secret").unwrap(), "from-file");
+        assert_eq!(config.get_string("server.host").unwrap(), "127.0.0.1");
+    }
+
+    #[test]
+    fn missing_file_ref_names_the_key() {
+        let config = Config::builder()
+            .set_override("database.password", "file:/nonexistent/db_password")
+            .unwrap()
+            .build()
+            .unwrap();
+        let error = resolve_file_refs(config).unwrap_err().to_string();
+        assert!(error.contains("/nonexistent/db_password"), "{error}");
+        assert!(error.contains("database.password"), "{error}");
+    }
+
+    #[test]
+    fn debug_and_redacted_config_hide_secrets() {
+        let secret = Secret::new("hunter2");
+        assert_eq!(format!("{secret:?}"), "\"***\"");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"hunter2\"");
+
+        let mut config = AppConfig::read().unwrap();
+        config.jwt.secret = secret.clone();
+        config.database.password = Secret::new("db-hunter2");
+        config.database.url = Some(Secret::new("postgres://u:url-hunter2@db/app"));
+        let debug = format!("{config:?}");
+        let json = serde_json::to_string(&config.redacted()).unwrap();
+        for output in [debug, json] {
+            assert!(!output.contains("hunter2"), "{output}");
+        }
+    }
+}
@@ -1,11 +1,69 @@
 use std::net::{AddrParseError, SocketAddr};
 
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[schemars(deny_unknown_fields)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// 同时处理的最大请求数，超出后直接返回 503，不配置则不限制
+    #[serde(default)]
+    pub max_in_flight: Option<usize>,
+    /// 返回 503 时 `Retry-After` 头的秒数
+    #[serde(default = "default_retry_after_secs")]
+    pub retry_after_secs: u64,
+    /// 就绪检查中每一项检查的超时时间(毫秒)
+    #[serde(default = "default_readiness_timeout_ms")]
+    pub readiness_timeout_ms: u64,
+    /// 收到停机信号后先将就绪检查置为失败，等待该秒数再停止接收请求
+    #[serde(default)]
+    pub shutdown_delay_secs: u64,
+    /// 跨域配置
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// 配置目录中的文件变化时自动重新加载配置，SIGHUP 不受该配置影响
+    #[serde(default = "default_watch_config")]
+    pub watch_config: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[schemars(deny_unknown_fields)]
+pub struct CorsConfig {
+    /// 允许跨域访问的来源，例如 `https://example.com`，`*` 表示允许所有来源
+    #[serde(default = "default_allow_origins")]
+    pub allow_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self { allow_origins: default_allow_origins() }
+    }
+}
+
+impl CorsConfig {
+    pub fn allows(&self, origin: &str) -> bool {
+        self.allow_origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed.eq_ignore_ascii_case(origin))
+    }
+}
+
+fn default_allow_origins() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+fn default_watch_config() -> bool {
+    true
+}
+
+fn default_retry_after_secs() -> u64 {
+    1
+}
+
+fn default_readiness_timeout_ms() -> u64 {
+    1000
 }
 
 impl ServerConfig {
@@ -21,4 +79,9 @@ impl ServerConfig {
     pub fn get_socket_addr(&self) -> Result<SocketAddr, AddrParseError> {
         self.get_addr()?.parse()
     }
+
+    /// 同一 host 上其他端口的地址，例如管理端口
+    pub fn get_socket_addr_with_port(&self, port: u16) -> Result<SocketAddr, AddrParseError> {
+        format!("{}:{}", self.host, port).parse()
+    }
 }
@@ -0,0 +1,324 @@
+use std::{fmt, str::FromStr};
+
+use sqlx::postgres::PgConnectOptions;
+use tracing_subscriber::EnvFilter;
+
+use crate::{layers::ConfigSources, AppConfig};
+
+/// 常见的示例/默认密钥，出现在非开发环境中视为错误
+const DEFAULT_SECRETS: [&str; 5] =
+    ["thisismysecret", "secret", "changeme", "password", "jwt_secret"];
+
+/// JWT 密钥的最小长度
+const MIN_SECRET_LEN: usize = 32;
+
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// 服务仍可启动，启动后记录警告日志
+    Warning,
+    /// 服务拒绝启动
+    Error,
+}
+
+/// 配置中的一个问题，`path` 为配置项路径，例如 `jwt.secret`
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{level}: {}: {}", self.path, self.message)
+    }
+}
+
+/// 一次配置检查发现的所有问题
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReport {
+    pub problems: Vec<ConfigProblem>,
+    /// 参与合并的配置源，以及每个配置项的来源
+    pub sources: ConfigSources,
+}
+
+impl ConfigReport {
+    pub fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.push(Severity::Error, path, message);
+    }
+
+    pub fn warning(&mut self, path: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, path, message);
+    }
+
+    fn push(&mut self, severity: Severity, path: &str, message: impl Into<String>) {
+        self.problems.push(ConfigProblem {
+            severity,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.problems.iter().any(|p| p.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigProblem> {
+        self.problems.iter().filter(|p| p.severity == Severity::Warning)
+    }
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.problems.iter().filter(|p| p.severity == Severity::Error).count();
+        writeln!(f, "invalid configuration, {errors} error(s):")?;
+        for problem in &self.problems {
+            writeln!(f, "  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl AppConfig {
+    /// 检查配置的语义问题，返回所有发现的问题而不是遇到第一个就停止
+    pub fn validate(&self, report: &mut ConfigReport) {
+        let development = self.profile.is_development();
+
+        // 服务地址
+        if self.server.host.trim().is_empty() {
+            report.error("server.host", "must not be empty");
+        } else if self.server.get_socket_addr().is_err() {
+            report
+                .error("server.host", format!("`{}` is not a valid IP address", self.server.host));
+        }
+        if self.server.port == 0 {
+            report.error("server.port", "must be between 1 and 65535");
+        }
+        if let Some(admin_port) = self.metrics.admin_port {
+            if admin_port == 0 {
+                report.error("metrics.admin_port", "must be between 1 and 65535");
+            } else if admin_port == self.server.port {
+                report.error("metrics.admin_port", "must differ from server.port");
+            }
+        }
+        let admin_allow =
+            self.network.access.get("admin").is_some_and(|access| !access.allow.is_empty());
+        if self.metrics.enabled && self.metrics.admin_port.is_none() && !admin_allow {
+            report.warning(
+                "metrics.admin_port",
+                "not set and network.access.admin.allow is empty, /metrics denies every client",
+            );
+        }
+        if self.server.max_in_flight == Some(0) {
+            report.error("server.max_in_flight", "must be greater than 0");
+        }
+        for origin in &self.server.cors.allow_origins {
+            let valid = origin == "*"
+                || ((origin.starts_with("http://") || origin.starts_with("https://"))
+                    && !origin.ends_with('/'));
+            if !valid {
+                report.error(
+                    "server.cors.allow_origins",
+                    format!(
+                        "`{origin}` must be `*` or a scheme and host such as https://example.com"
+                    ),
+                );
+            }
+        }
+
+        // 数据库
+        let database = &self.database;
+        match &database.url {
+            Some(url) => {
+                let url = url.expose();
+                if !(url.starts_with("postgres://") || url.starts_with("postgresql://")) {
+                    report.error("database.url", "must start with postgres:// or postgresql://");
+                } else if let Err(e) = PgConnectOptions::from_str(url) {
+                    // 错误信息不包含 URL 本身，不会泄露密码
+                    report.error("database.url", format!("invalid url: {e}"));
+                }
+            }
+            None => {
+                if database.host.trim().is_empty() {
+                    report.error("database.host", "must not be empty");
+                }
+                if database.port == 0 {
+                    report.error("database.port", "must be between 1 and 65535");
+                }
+                if database.username.trim().is_empty() {
+                    report.error("database.username", "must not be empty");
+                }
+                if database.database_name.trim().is_empty() {
+                    report.error("database.database_name", "must not be empty");
+                }
+            }
+        }
+        if database.max_connections == 0 {
+            report.error("database.max_connections", "must be greater than 0");
+        } else if database.min_connections > database.max_connections {
+            report.error("database.min_connections", "must not exceed database.max_connections");
+        }
+        if let Some(cert) = &database.ssl_root_cert {
+            if !std::path::Path::new(cert).is_file() {
+                report.error("database.ssl_root_cert", format!("`{cert}` does not exist"));
+            }
+        }
+        if !development
+            && database.url.is_none()
+            && (database.password.is_empty() || database.password.expose() == "postgres")
+        {
+            report.warning("database.password", "uses an empty or default password");
+        }
+        for (path, level) in [
+            ("database.log_statements", &database.log_statements),
+            ("database.log_slow_statements", &database.log_slow_statements),
+        ] {
+            if !LOG_LEVELS.contains(&level.to_lowercase().as_str()) {
+                report.error(path, format!("`{level}` is not one of {}", LOG_LEVELS.join(" / ")));
+            }
+        }
+
+        // JWT
+        let secret = self.jwt.secret.expose();
+        if secret.is_empty() {
+            report.error("jwt.secret", "must be set");
+        } else if !development {
+            if DEFAULT_SECRETS.contains(&secret.to_lowercase().as_str()) {
+                report.error("jwt.secret", "uses a well-known default value");
+            } else if secret.len() < MIN_SECRET_LEN {
+                report.error(
+                    "jwt.secret",
+                    format!("must be at least {MIN_SECRET_LEN} characters outside development"),
+                );
+            }
+        }
+        if self.jwt.expired <= 0 {
+            report.error("jwt.expired", "must be greater than 0");
+        }
+
+        // 功能开关
+        for (name, flag) in &self.features.flags {
+            let valid_name = !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+            if !valid_name {
+                report.error(
+                    &format!("features.flags.{name}"),
+                    "name may only contain letters, digits, `_`, `-` and `.`",
+                );
+            }
+            if flag.percentage.is_some_and(|p| p > 100) {
+                report.error(
+                    &format!("features.flags.{name}.percentage"),
+                    "must be between 0 and 100",
+                );
+            }
+        }
+
+        // 日志与链路追踪
+        if let Err(e) = EnvFilter::try_new(self.tracing.filter_directive()) {
+            report.error("tracing.log_level", format!("invalid log filter: {e}"));
+        }
+        if !(0.0..=1.0).contains(&self.otlp.sampling_ratio) {
+            report.error("otlp.sampling_ratio", "must be between 0.0 and 1.0");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::{profile::Profile, secret::Secret};
+
+    /// 项目 `setting/development.toml` 中的配置
+    fn config(profile: &str) -> AppConfig {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../setting/development.toml");
+        let mut config: AppConfig = config::Config::builder()
+            .add_source(config::File::from(path))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        config.profile = Profile::new(profile).unwrap();
+        config
+    }
+
+    fn check(config: &AppConfig) -> ConfigReport {
+        let mut report = ConfigReport::default();
+        config.validate(&mut report);
+        report
+    }
+
+    fn paths(report: &ConfigReport, severity: Severity) -> Vec<&str> {
+        report.problems.iter().filter(|p| p.severity == severity).map(|p| p.path.as_str()).collect()
+    }
+
+    #[test]
+    fn development_config_is_valid() {
+        let report = check(&config(Profile::DEVELOPMENT));
+        assert!(report.problems.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        let mut config = config(Profile::DEVELOPMENT);
+        config.server.port = 0;
+        config.database.max_connections = 0;
+        config.otlp.sampling_ratio = 1.5;
+        let report = check(&config);
+
+        assert!(report.has_errors());
+        assert_eq!(
+            paths(&report, Severity::Error),
+            ["server.port", "database.max_connections", "otlp.sampling_ratio"]
+        );
+        let text = report.to_string();
+        assert!(text.starts_with("invalid configuration, 3 error(s):"));
+        assert!(text.contains("  - error: server.port: must be between 1 and 65535"));
+    }
+
+    #[test]
+    fn secrets_are_strict_outside_development() {
+        let report = check(&config(Profile::PRODUCTION));
+        assert_eq!(paths(&report, Severity::Error), ["jwt.secret"]);
+        assert_eq!(paths(&report, Severity::Warning), ["database.password"]);
+
+        let mut config = config(Profile::PRODUCTION);
+        config.jwt.secret = Secret::new("short-but-not-default");
+        let report = check(&config);
+        let secret = report.problems.iter().find(|p| p.path == "jwt.secret").unwrap();
+        assert!(secret.message.starts_with("must be at least 32 characters"));
+    }
+
+    #[test]
+    fn rejects_unparsable_database_settings() {
+        let mut config = config(Profile::DEVELOPMENT);
+        config.database.url = Some(Secret::new("postgres://app:hunter2@db:notaport/app"));
+        config.database.log_slow_statements = "loud".to_string();
+        let report = check(&config);
+
+        assert_eq!(
+            paths(&report, Severity::Error),
+            ["database.url", "database.log_slow_statements"]
+        );
+        assert!(!report.to_string().contains("hunter2"), "{report}");
+    }
+
+    #[test]
+    fn warnings_do_not_block_startup() {
+        let mut config = config(Profile::DEVELOPMENT);
+        config.network.access.remove("admin");
+        let report = check(&config);
+
+        assert!(!report.has_errors());
+        let warnings: Vec<_> = report.warnings().map(|p| p.path.as_str()).collect();
+        assert_eq!(warnings, ["metrics.admin_port"]);
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::FORWARDED, request::Parts, HeaderMap, HeaderName, Request},
+};
+use configure::{
+    error::AppError,
+    network::{ForwardedHeader, NetworkConfig},
+    reload::LiveConfig,
+};
+use tower::{Layer, Service};
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// 解析后的真实客户端 IP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<ClientIp>()
+            .copied()
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("client ip is unavailable")))
+    }
+}
+
+/// 解析客户端 IP：只有直连地址是受信任代理时才读取转发头，
+/// 从右往左跳过受信任代理，第一个不受信任的地址即为客户端地址
+pub fn resolve_client_ip(peer: IpAddr, headers: &HeaderMap, network: &NetworkConfig) -> IpAddr {
+    if !network.is_trusted_proxy(&peer) {
+        return peer;
+    }
+    let chain = forwarded_chain(headers, network.forwarded_header);
+    chain
+        .iter()
+        .rev()
+        .find(|ip| !network.is_trusted_proxy(ip))
+        .or_else(|| chain.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
+/// 按从左到右的顺序返回转发链，只读取代理实际设置的转发头
+fn forwarded_chain(headers: &HeaderMap, header: ForwardedHeader) -> Vec<IpAddr> {
+    let name = match header {
+        ForwardedHeader::XForwardedFor => X_FORWARDED_FOR,
+        ForwardedHeader::Forwarded => FORWARDED,
+    };
+    let nodes =
+        headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).flat_map(|v| v.split(','));
+    match header {
+        ForwardedHeader::XForwardedFor => {
+            nodes.filter_map(|node| parse_node(node.trim())).collect()
+        }
+        ForwardedHeader::Forwarded => nodes.filter_map(parse_forwarded_for).collect(),
+    }
+}
+
+/// 解析 `Forwarded` 头中的一个元素，例如 `for=192.0.2.60;proto=http`
+fn parse_forwarded_for(element: &str) -> Option<IpAddr> {
+    element.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        if !key.eq_ignore_ascii_case("for") {
+            return None;
+        }
+        parse_node(value.trim().trim_matches('"'))
+    })
+}
+
+/// 解析节点地址，支持 `1.2.3.4`、`1.2.3.4:80`、`[2001:db8::1]:80` 等形式
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    node.parse().ok().or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// 解析客户端 IP 并写入请求扩展，需要使用 `into_make_service_with_connect_info` 启动服务
+#[derive(Clone)]
+pub struct ClientIpLayer {
+    config: LiveConfig,
+}
+
+impl ClientIpLayer {
+    pub fn new(config: LiveConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for ClientIpLayer {
+    type Service = ClientIpMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientIpMiddleware { inner, config: self.config.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientIpMiddleware<S> {
+    inner: S,
+    config: LiveConfig,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for ClientIpMiddleware<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+        if let Some(peer) = peer {
+            let ip = resolve_client_ip(peer, req.headers(), &self.config.load().network);
+            req.extensions_mut().insert(ClientIp(ip));
+        }
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const PEER: &str = "10.0.0.1";
+
+    fn network(header: ForwardedHeader) -> NetworkConfig {
+        NetworkConfig {
+            trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+            forwarded_header: header,
+            ..Default::default()
+        }
+    }
+
+    fn resolve(headers: &[(&str, &str)], network: &NetworkConfig) -> IpAddr {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(
+                HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        resolve_client_ip(PEER.parse().unwrap(), &map, network)
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ignores_headers_from_untrusted_peer() {
+        let network = network(ForwardedHeader::XForwardedFor);
+        let map = HeaderMap::from_iter([(X_FORWARDED_FOR, HeaderValue::from_static("10.0.0.9"))]);
+        assert_eq!(resolve_client_ip(ip("203.0.113.7"), &map, &network), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn ignores_forwarded_when_proxy_sets_x_forwarded_for() {
+        let network = network(ForwardedHeader::XForwardedFor);
+        let headers = [("forwarded", "for=10.0.0.1"), ("x-forwarded-for", "203.0.113.7")];
+        assert_eq!(resolve(&headers, &network), ip("203.0.113.7"));
+        // 代理没有添加 X-Forwarded-For 时使用直连地址，而不是客户端伪造的 Forwarded
+        assert_eq!(resolve(&[("forwarded", "for=10.0.0.1")], &network), ip(PEER));
+    }
+
+    #[test]
+    fn ignores_x_forwarded_for_when_proxy_sets_forwarded() {
+        let network = network(ForwardedHeader::Forwarded);
+        let headers = [("x-forwarded-for", "10.0.0.1"), ("forwarded", "for=\"[2001:db8::1]:443\"")];
+        assert_eq!(resolve(&headers, &network), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn takes_rightmost_untrusted_hop() {
+        let network = network(ForwardedHeader::XForwardedFor);
+        // 客户端伪造的最左边的地址被忽略
+        let headers = [("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.2")];
+        assert_eq!(resolve(&headers, &network), ip("203.0.113.7"));
+        let headers =
+            [("x-forwarded-for", "198.51.100.1"), ("x-forwarded-for", "203.0.113.7:8080")];
+        assert_eq!(resolve(&headers, &network), ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn all_trusted_hops_fall_back_to_leftmost() {
+        let network = network(ForwardedHeader::XForwardedFor);
+        assert_eq!(resolve(&[("x-forwarded-for", "10.0.0.3, 10.0.0.2")], &network), ip("10.0.0.3"));
+    }
+}
@@ -1,7 +1,7 @@
-use axum::{
-    async_trait,
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use configure::{
+    error::{AppError, ErrorCode},
+    jwt::ADMIN_ROLE,
 };
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,16 @@ pub struct LoginUser {
     pub user_id: String,
     pub username: String,
     pub exp: i64,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+impl LoginUser {
+    pub fn is_admin(&self) -> bool {
+        self.role.as_deref() == Some(ADMIN_ROLE)
+    }
 }
 
 #[async_trait]
@@ -18,13 +28,33 @@ impl<S> FromRequestParts<S> for LoginUser
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         parts
             .extensions
             .get::<LoginUser>()
             .cloned()
-            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid or missing JWT".to_string()))
+            .ok_or_else(|| AppError::coded(ErrorCode::AuthTokenMissing, "Invalid or missing JWT"))
+    }
+}
+
+/// 管理员用户，非管理员访问时返回 403
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub LoginUser);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = LoginUser::from_request_parts(parts, state).await?;
+        if !user.is_admin() {
+            return Err(AppError::coded(ErrorCode::AdminRequired, "Admin role required"));
+        }
+        Ok(AdminUser(user))
     }
 }
@@ -0,0 +1,56 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::http::Request;
+use configure::error;
+use tower::{Layer, Service};
+
+/// 将是否返回内部错误细节的设置放入任务上下文，供 `AppError` 生成错误响应
+///
+/// 设置属于应用实例，同一进程中的多个实例可以不同
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ErrorDetailsLayer {
+    expose: bool,
+}
+
+impl ErrorDetailsLayer {
+    pub fn new(expose: bool) -> Self {
+        Self { expose }
+    }
+}
+
+impl<S> Layer<S> for ErrorDetailsLayer {
+    type Service = ErrorDetails<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ErrorDetails { inner, expose: self.expose }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorDetails<S> {
+    inner: S,
+    expose: bool,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for ErrorDetails<S>
+where
+    S: Service<Request<ReqBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let fut = self.inner.call(req);
+        Box::pin(error::expose_internal_errors_scope(self.expose, fut))
+    }
+}
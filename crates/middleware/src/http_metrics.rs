@@ -0,0 +1,95 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use axum::{extract::MatchedPath, http::Request, response::Response};
+use metrics::{counter, gauge, histogram};
+use tower::{Layer, Service};
+
+/// 未匹配到路由的请求统一使用该标签，避免标签基数失控
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// 按路由和状态码记录请求数、耗时和正在处理的请求数
+#[derive(Debug, Clone, Default)]
+pub struct HttpMetricsLayer;
+
+impl<S> Layer<S> for HttpMetricsLayer {
+    type Service = HttpMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpMetrics { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpMetrics<S> {
+    inner: S,
+}
+
+/// 请求结束或被取消时减少 in-flight 计数
+struct InFlightGuard {
+    method: String,
+    route: String,
+}
+
+impl InFlightGuard {
+    fn new(method: String, route: String) -> Self {
+        gauge!("http_requests_in_flight", "method" => method.clone(), "route" => route.clone())
+            .increment(1.0);
+        Self { method, route }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        gauge!(
+            "http_requests_in_flight",
+            "method" => self.method.clone(),
+            "route" => self.route.clone()
+        )
+        .decrement(1.0);
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for HttpMetrics<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let method = req.method().to_string();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+        let start = Instant::now();
+        let guard = InFlightGuard::new(method.clone(), route.clone());
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let res = fut.await;
+            drop(guard);
+            let status = match &res {
+                Ok(resp) => resp.status().as_u16().to_string(),
+                Err(_) => "error".to_string(),
+            };
+            let labels = [("method", method), ("route", route), ("status", status)];
+            counter!("http_requests_total", &labels).increment(1);
+            histogram!("http_request_duration_seconds", &labels)
+                .record(start.elapsed().as_secs_f64());
+            res
+        })
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    http::Request,
+    response::{IntoResponse, Response},
+};
+use configure::{
+    error::{AppError, ErrorCode},
+    reload::LiveConfig,
+};
+use tower::{Layer, Service};
+use tracing::warn;
+
+use crate::client_ip::ClientIp;
+
+/// 按 `[network.access.<group>]` 的网段允许/拒绝访问，依赖 `ClientIpLayer` 解析出的客户端 IP
+#[derive(Clone)]
+pub struct IpFilterLayer {
+    config: LiveConfig,
+    group: Arc<str>,
+    require_allow: bool,
+}
+
+impl IpFilterLayer {
+    pub fn new(config: LiveConfig, group: &str) -> Self {
+        Self { config, group: group.into(), require_allow: false }
+    }
+
+    /// 分组没有配置 allow 列表时拒绝所有请求，而不是不做限制
+    pub fn require_allow(mut self) -> Self {
+        self.require_allow = true;
+        self
+    }
+}
+
+impl<S> Layer<S> for IpFilterLayer {
+    type Service = IpFilter<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IpFilter {
+            inner,
+            config: self.config.clone(),
+            group: self.group.clone(),
+            require_allow: self.require_allow,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IpFilter<S> {
+    inner: S,
+    config: LiveConfig,
+    group: Arc<str>,
+    require_allow: bool,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for IpFilter<S>
+where
+    S: Service<Request<ReqBody>, Response = Response, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // 未配置该分组时不做限制(要求 allow 列表时拒绝)，无法确定客户端 IP 时，配置了 allow 或 deny
+        // 列表就拒绝
+        let allowed = match self.config.load().network.access.get(&*self.group) {
+            None => !self.require_allow,
+            Some(access) if access.allow.is_empty() && self.require_allow => false,
+            Some(access) => match req.extensions().get::<ClientIp>() {
+                Some(ClientIp(ip)) => access.is_allowed(ip),
+                None => access.allow.is_empty() && access.deny.is_empty(),
+            },
+        };
+        if allowed {
+            return Box::pin(self.inner.call(req));
+        }
+
+        warn!(client_ip = ?req.extensions().get::<ClientIp>(), uri = %req.uri(), "ip access denied");
+        Box::pin(async move {
+            Ok(AppError::coded(ErrorCode::IpDenied, "Access denied from this address")
+                .into_response())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::StatusCode};
+    use configure::{network::IpAccessConfig, AppConfig};
+    use tower::{service_fn, ServiceExt};
+
+    use super::*;
+
+    async fn status(access: IpAccessConfig, client_ip: Option<&str>) -> StatusCode {
+        let mut config = AppConfig::read().unwrap();
+        config.network.access.insert("api".to_string(), access);
+        let inner = service_fn(|_: Request<Body>| async {
+            Ok::<_, Infallible>(StatusCode::OK.into_response())
+        });
+        let svc = IpFilterLayer::new(LiveConfig::new(config), "api").layer(inner);
+        let mut req = Request::builder().uri("/users").body(Body::empty()).unwrap();
+        if let Some(ip) = client_ip {
+            req.extensions_mut().insert(ClientIp(ip.parse().unwrap()));
+        }
+        svc.oneshot(req).await.unwrap().status()
+    }
+
+    fn access(allow: &[&str], deny: &[&str]) -> IpAccessConfig {
+        let nets = |nets: &[&str]| nets.iter().map(|net| net.parse().unwrap()).collect();
+        IpAccessConfig { allow: nets(allow), deny: nets(deny) }
+    }
+
+    #[tokio::test]
+    async fn unknown_ip_is_denied_when_a_deny_list_is_set() {
+        assert_eq!(status(access(&[], &["10.0.0.0/8"]), None).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(access(&["10.0.0.0/8"], &[]), None).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(access(&[], &[]), None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn deny_wins_over_allow() {
+        let access = || access(&["10.0.0.0/8"], &["10.1.0.0/16"]);
+        assert_eq!(status(access(), Some("10.2.0.1")).await, StatusCode::OK);
+        assert_eq!(status(access(), Some("10.1.0.1")).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(access(), Some("192.168.0.1")).await, StatusCode::FORBIDDEN);
+    }
+}
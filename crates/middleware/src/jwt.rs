@@ -10,8 +10,12 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use chrono::Utc;
-use configure::{error::AppError, CONFIG};
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use configure::{
+    error::{AppError, ErrorCode},
+    jwt::JwtConfig,
+};
+use jsonwebtoken::{decode, errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use metrics::counter;
 use serde::{Deserialize, Serialize};
 use tower::{Layer, Service};
 use tracing::error;
@@ -25,23 +29,43 @@ pub struct Claims {
     pub username: String,
     pub user_id: String,
     pub exp: i64,
+    /// 用户角色，例如 `admin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// 用户所属租户，用于功能开关按租户开启
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 impl Claims {
     /// 构建Claims
-    pub fn build(sub: &str, user_id: &str, username: &str) -> Self {
-        let token_exp = Utc::now().timestamp() + (CONFIG.jwt.expired as i64) * 3600;
+    pub fn build(config: &JwtConfig, sub: &str, user_id: &str, username: &str) -> Self {
+        let token_exp = Utc::now().timestamp() + (config.expired as i64) * 3600;
         Claims {
             sub: sub.to_string(),
             username: username.to_string(),
             user_id: user_id.to_string(),
             exp: token_exp,
+            role: None,
+            tenant: None,
         }
     }
 
+    /// 设置用户角色
+    pub fn with_role(mut self, role: &str) -> Self {
+        self.role = Some(role.to_string());
+        self
+    }
+
+    /// 设置用户所属租户
+    pub fn with_tenant(mut self, tenant: &str) -> Self {
+        self.tenant = Some(tenant.to_string());
+        self
+    }
+
     /// 生成JWT token
-    pub fn to_token(&self) -> Result<String, jsonwebtoken::errors::Error> {
-        let jwt_secret = &CONFIG.jwt.secret;
+    pub fn to_token(&self, config: &JwtConfig) -> Result<String, jsonwebtoken::errors::Error> {
+        let jwt_secret = config.secret.expose();
         let encoding_key = jsonwebtoken::EncodingKey::from_secret(jwt_secret.as_bytes());
         let mut header = jsonwebtoken::Header::default();
         header.alg = jsonwebtoken::Algorithm::HS256;
@@ -49,17 +73,25 @@ impl Claims {
     }
 
     pub fn to_login_user(&self) -> LoginUser {
-        LoginUser { user_id: self.user_id.clone(), username: self.username.clone(), exp: self.exp }
+        LoginUser {
+            user_id: self.user_id.clone(),
+            username: self.username.clone(),
+            exp: self.exp,
+            role: self.role.clone(),
+            tenant: self.tenant.clone(),
+        }
     }
 }
 
 /// JWT中间件层
 #[derive(Clone)]
-pub struct JwtLayer;
+pub struct JwtLayer {
+    decoding_key: DecodingKey,
+}
 
 impl JwtLayer {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: &JwtConfig) -> Self {
+        Self { decoding_key: DecodingKey::from_secret(config.secret.expose().as_bytes()) }
     }
 }
 
@@ -67,7 +99,7 @@ impl<S> Layer<S> for JwtLayer {
     type Service = JwtMiddleware<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        JwtMiddleware { inner }
+        JwtMiddleware { inner, decoding_key: self.decoding_key.clone() }
     }
 }
 
@@ -75,6 +107,7 @@ impl<S> Layer<S> for JwtLayer {
 #[derive(Clone)]
 pub struct JwtMiddleware<S> {
     inner: S,
+    decoding_key: DecodingKey,
 }
 
 impl<S, ReqBody> Service<Request<ReqBody>> for JwtMiddleware<S>
@@ -93,6 +126,7 @@ where
 
     fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
         let mut inner = self.inner.clone();
+        let decoding_key = self.decoding_key.clone();
 
         Box::pin(async move {
             let token = req
@@ -102,11 +136,12 @@ where
                 .and_then(|h| h.strip_prefix("Bearer ").or_else(|| Some(h)));
 
             if token.is_none() {
-                let resp = AppError::Unauthorized("Missing token".into()).into_response();
+                counter!("jwt_rejections_total", "reason" => "missing").increment(1);
+                let resp =
+                    AppError::coded(ErrorCode::AuthTokenMissing, "Missing token").into_response();
                 return Ok(resp);
             }
 
-            let decoding_key = DecodingKey::from_secret(CONFIG.jwt.secret.as_bytes());
             let validation = Validation::new(Algorithm::HS256);
 
             match decode::<Claims>(token.unwrap(), &decoding_key, &validation) {
@@ -116,8 +151,15 @@ where
                     inner.call(req).await
                 }
                 Err(e) => {
-                    let resp =
-                        AppError::Unauthorized(format!("Invalid token: {e}")).into_response();
+                    let (reason, code) = match e.kind() {
+                        ErrorKind::ExpiredSignature => ("expired", ErrorCode::AuthTokenExpired),
+                        ErrorKind::InvalidSignature => {
+                            ("invalid_signature", ErrorCode::AuthTokenInvalid)
+                        }
+                        _ => ("invalid", ErrorCode::AuthTokenInvalid),
+                    };
+                    counter!("jwt_rejections_total", "reason" => reason).increment(1);
+                    let resp = AppError::coded(code, format!("Invalid token: {e}")).into_response();
                     error!("JWT Error: {}", e);
                     Ok(resp)
                 }
@@ -1,40 +1,90 @@
 use axum::Router;
+use configure::{jwt::JwtConfig, maintenance::Maintenance, redact::Redactor, reload::LiveConfig};
 use tower::ServiceBuilder;
 
-use crate::jwt::JwtLayer;
+use crate::{
+    client_ip::ClientIpLayer,
+    error_details::ErrorDetailsLayer,
+    http_metrics::HttpMetricsLayer,
+    ip_filter::IpFilterLayer,
+    jwt::JwtLayer,
+    load_shed::LoadShedLayer,
+    maintenance::MaintenanceLayer,
+    panic::catch_panic_layer,
+    request_id::{RequestIdMakeSpan, RequestIdScopeLayer},
+    trace_context::TraceContextLayer,
+};
+pub mod client_ip;
 pub mod ctx;
+pub mod error_details;
+pub mod extract;
+pub mod http_metrics;
+pub mod ip_filter;
 pub mod jwt;
+pub mod load_shed;
+pub mod maintenance;
+pub mod panic;
+pub mod request_id;
+pub mod trace_context;
 
 /// Simple request-id + trace layer using tower-http's request_id feature
-pub fn apply(router: Router) -> Router {
+pub fn apply(router: Router, config: &LiveConfig, maintenance: Maintenance) -> Router {
     use tower_http::{
-        cors::CorsLayer,
+        cors::{AllowOrigin, CorsLayer},
         request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
-        trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
+        trace::{DefaultOnResponse, TraceLayer},
     };
 
-    // Trace HTTP traffic
+    // Trace HTTP traffic, the span carries the request id
     let trace = TraceLayer::new_for_http()
-        .make_span_with(DefaultMakeSpan::new().include_headers(true))
+        .make_span_with(RequestIdMakeSpan::new(Redactor::new(&config.load().tracing.redact)))
         .on_response(DefaultOnResponse::new());
 
     // Set & propagate request IDs
     let req_id = SetRequestIdLayer::x_request_id(MakeRequestUuid);
     let propagate = PropagateRequestIdLayer::x_request_id();
 
-    // Set cors headers
+    // Set cors headers, allowed origins are read from the live config on every request
+    let live = config.clone();
+    let allow_origin = AllowOrigin::predicate(move |origin, _| {
+        origin.to_str().is_ok_and(|origin| live.load().server.cors.allows(origin))
+    });
     let core = CorsLayer::new()
-        .allow_origin(tower_http::cors::Any)
+        .allow_origin(allow_origin)
         .allow_methods(tower_http::cors::Any)
         .allow_headers(tower_http::cors::Any);
 
-    //build the middleware stack
+    //build the middleware stack, request id and client ip must be set before the trace span is
+    // created. cors wraps the panic, maintenance and load-shed layers so that their error
+    // responses carry the cors headers too, and preflight requests are answered before them
     let layer = ServiceBuilder::new()
-        .layer(trace)
         .layer(req_id)
+        .layer(ClientIpLayer::new(config.clone()))
+        .layer(trace)
+        .layer(TraceContextLayer)
+        .layer(HttpMetricsLayer)
         .layer(propagate)
+        .layer(RequestIdScopeLayer)
+        .layer(ErrorDetailsLayer::new(config.load().profile.exposes_internal_errors()))
         .layer(core)
-        .layer(JwtLayer::new());
+        .layer(catch_panic_layer())
+        .layer(MaintenanceLayer::new(maintenance))
+        .layer(LoadShedLayer::new(config.clone()));
 
     router.layer(layer)
 }
+
+/// IP allow/deny lists for a route group, configured under `[network.access.<group>]`
+pub fn apply_access(router: Router, config: &LiveConfig, group: &str) -> Router {
+    router.layer(IpFilterLayer::new(config.clone(), group))
+}
+
+/// Like `apply_access`, but denies every request until `[network.access.<group>].allow` is set
+pub fn apply_allow_list(router: Router, config: &LiveConfig, group: &str) -> Router {
+    router.layer(IpFilterLayer::new(config.clone(), group).require_allow())
+}
+
+/// JWT authentication layer for protected routes
+pub fn apply_auth(router: Router, jwt: &JwtConfig) -> Router {
+    router.layer(JwtLayer::new(jwt))
+}
@@ -0,0 +1,208 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use axum::{
+    http::{header::RETRY_AFTER, Request},
+    response::{IntoResponse, Response},
+};
+use configure::{
+    error::{AppError, ErrorCode},
+    maintenance::matches_any_prefix,
+    reload::LiveConfig,
+};
+use metrics::counter;
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// 不受并发限制的路径，过载时存活和就绪检查仍然要能响应，否则编排系统会重启忙碌但健康的实例
+const EXEMPT_PREFIXES: [&str; 1] = ["/health"];
+
+/// 全局并发请求限制，容量耗尽时立即返回 503 而不是排队等待
+///
+/// 上限每次请求从 `server.max_in_flight` 读取，重新加载配置后立即生效
+#[derive(Clone)]
+pub struct LoadShedLayer {
+    config: LiveConfig,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl LoadShedLayer {
+    pub fn new(config: LiveConfig) -> Self {
+        Self { config, in_flight: Arc::new(AtomicUsize::new(0)) }
+    }
+}
+
+impl<S> Layer<S> for LoadShedLayer {
+    type Service = LoadShed<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoadShed { inner, config: self.config.clone(), in_flight: self.in_flight.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct LoadShed<S> {
+    inner: S,
+    config: LiveConfig,
+    in_flight: Arc<AtomicUsize>,
+}
+
+/// 占用一个并发名额，drop 时归还，请求被取消时同样会归还
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    /// 未达到上限时占用一个名额，被拒绝的请求不会改变计数
+    fn try_acquire(in_flight: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < max).then_some(n + 1))
+            .ok()?;
+        Some(Self(in_flight.clone()))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for LoadShed<S>
+where
+    S: Service<Request<ReqBody>, Response = Response, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let (max_in_flight, retry_after) = {
+            let server = &self.config.load().server;
+            (server.max_in_flight, server.retry_after_secs)
+        };
+        let Some(max_in_flight) = max_in_flight else {
+            return Box::pin(self.inner.call(req));
+        };
+        if matches_any_prefix(req.uri().path(), &EXEMPT_PREFIXES) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        if let Some(permit) = InFlight::try_acquire(&self.in_flight, max_in_flight) {
+            let fut = self.inner.call(req);
+            return Box::pin(async move {
+                let res = fut.await;
+                drop(permit);
+                res
+            });
+        }
+
+        warn!("too many in-flight requests, shedding load");
+        counter!("http_requests_shed_total").increment(1);
+        Box::pin(async move {
+            let mut resp = AppError::coded(
+                ErrorCode::ServerOverloaded,
+                "Server is overloaded, please retry later",
+            )
+            .into_response();
+            resp.headers_mut().insert(RETRY_AFTER, retry_after.into());
+            Ok(resp)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{body::Body, http::StatusCode};
+    use configure::AppConfig;
+    use tokio::sync::Semaphore;
+    use tower::{service_fn, ServiceExt};
+
+    use super::*;
+
+    /// 并发上限为 `max` 的中间件，内层服务在 `gate` 放行之前一直处理中
+    fn load_shed(
+        max: usize,
+        gate: Arc<Semaphore>,
+    ) -> (
+        Arc<AtomicUsize>,
+        LoadShed<
+            impl Service<Request<Body>, Response = Response, Error = Infallible, Future: Send> + Clone,
+        >,
+    ) {
+        let mut config = AppConfig::read().unwrap();
+        config.server.max_in_flight = Some(max);
+        config.server.retry_after_secs = 7;
+        let layer = LoadShedLayer::new(LiveConfig::new(config));
+        let inner = service_fn(move |_: Request<Body>| {
+            let gate = gate.clone();
+            async move {
+                let _permit = gate.acquire().await.unwrap();
+                Ok::<_, Infallible>(StatusCode::OK.into_response())
+            }
+        });
+        (layer.in_flight.clone(), layer.layer(inner))
+    }
+
+    fn request(path: &str) -> Request<Body> {
+        Request::builder().uri(path).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sheds_at_the_limit_with_retry_after() {
+        let gate = Arc::new(Semaphore::new(0));
+        let (in_flight, svc) = load_shed(1, gate.clone());
+
+        let pending = svc.clone().oneshot(request("/users"));
+        let pending = tokio::spawn(pending);
+        while in_flight.load(Ordering::Acquire) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let shed = svc.clone().oneshot(request("/users")).await.unwrap();
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(shed.headers()[RETRY_AFTER], "7");
+        // 被拒绝的请求不占用名额
+        assert_eq!(in_flight.load(Ordering::Acquire), 1);
+
+        gate.add_permits(1);
+        assert_eq!(pending.await.unwrap().unwrap().status(), StatusCode::OK);
+        assert_eq!(in_flight.load(Ordering::Acquire), 0);
+        gate.add_permits(1);
+        assert_eq!(svc.oneshot(request("/users")).await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn dropped_requests_release_their_slot() {
+        let (in_flight, svc) = load_shed(1, Arc::new(Semaphore::new(0)));
+        let pending = tokio::spawn(svc.clone().oneshot(request("/users")));
+        while in_flight.load(Ordering::Acquire) == 0 {
+            tokio::task::yield_now().await;
+        }
+        pending.abort();
+        let _ = pending.await;
+        assert_eq!(in_flight.load(Ordering::Acquire), 0);
+    }
+
+    #[tokio::test]
+    async fn health_checks_are_never_shed() {
+        let (_, svc) = load_shed(0, Arc::new(Semaphore::new(2)));
+        let live = svc.clone().oneshot(request("/health/live")).await.unwrap();
+        assert_eq!(live.status(), StatusCode::OK);
+        let users = svc.oneshot(request("/users")).await.unwrap();
+        assert_eq!(users.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::{
+    http::Request,
+    response::{IntoResponse, Response},
+};
+use configure::maintenance::Maintenance;
+use tower::{Layer, Service};
+
+/// 维护模式中间件层
+#[derive(Clone)]
+pub struct MaintenanceLayer {
+    maintenance: Maintenance,
+}
+
+impl MaintenanceLayer {
+    pub fn new(maintenance: Maintenance) -> Self {
+        Self { maintenance }
+    }
+}
+
+impl<S> Layer<S> for MaintenanceLayer {
+    type Service = MaintenanceMiddleware<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceMiddleware { inner, maintenance: self.maintenance.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct MaintenanceMiddleware<S> {
+    inner: S,
+    maintenance: Maintenance,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for MaintenanceMiddleware<S>
+where
+    S: Service<Request<ReqBody>, Response = Response, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        match self.maintenance.rejection(req.method(), req.uri().path()) {
+            Some(err) => Box::pin(async move { Ok(err.into_response()) }),
+            None => Box::pin(self.inner.call(req)),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::{any::Any, backtrace::Backtrace, cell::RefCell};
+
+use axum::response::{IntoResponse, Response};
+use configure::error::{request_id, AppError};
+use metrics::counter;
+use tower_http::catch_panic::CatchPanicLayer;
+use tracing::error;
+
+thread_local! {
+    /// panic hook 捕获的最近一次 backtrace，由 `handle_panic` 取出
+    static LAST_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// 安装 panic hook，在 unwind 之前记录 backtrace，之后仍调用原有的 hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        LAST_BACKTRACE.with(|bt| *bt.borrow_mut() = Some(Backtrace::force_capture()));
+        default_hook(info);
+    }));
+}
+
+/// 捕获 handler 中的 panic，返回标准的 500 JSON 错误响应
+pub fn catch_panic_layer() -> CatchPanicLayer<fn(Box<dyn Any + Send + 'static>) -> Response> {
+    CatchPanicLayer::custom(handle_panic)
+}
+
+fn handle_panic(payload: Box<dyn Any + Send + 'static>) -> Response {
+    counter!("http_panics_total").increment(1);
+
+    let message = if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    };
+    let backtrace = LAST_BACKTRACE
+        .with(|bt| bt.borrow_mut().take())
+        .map(|bt| bt.to_string())
+        .unwrap_or_default();
+
+    error!(
+        request_id = request_id::current().unwrap_or_default(),
+        panic = %message,
+        backtrace = %backtrace,
+        "request handler panicked"
+    );
+
+    AppError::Internal(anyhow::anyhow!("request handler panicked")).into_response()
+}
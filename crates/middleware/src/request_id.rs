@@ -0,0 +1,88 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use axum::http::{HeaderName, Request};
+use configure::{error::request_id, redact::Redactor};
+use tower::{Layer, Service};
+use tower_http::trace::MakeSpan;
+use tracing::Span;
+
+use crate::{client_ip::ClientIp, trace_context::set_parent_from_headers};
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 从请求头中读取 request id
+pub fn get_request_id<B>(req: &Request<B>) -> Option<&str> {
+    req.headers().get(&X_REQUEST_ID).and_then(|v| v.to_str().ok())
+}
+
+/// 创建带 request_id 字段的请求 span，日志中的每一行都会带上该字段
+///
+/// 敏感请求头和查询参数的值会被替换为占位符
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdMakeSpan {
+    redactor: Redactor,
+}
+
+impl RequestIdMakeSpan {
+    pub fn new(redactor: Redactor) -> Self {
+        Self { redactor }
+    }
+}
+
+impl<B> MakeSpan<B> for RequestIdMakeSpan {
+    fn make_span(&mut self, req: &Request<B>) -> Span {
+        let span = tracing::info_span!(
+            "request",
+            method = %req.method(),
+            uri = %self.redactor.uri(req.uri()),
+            version = ?req.version(),
+            request_id = get_request_id(req).unwrap_or_default(),
+            client_ip = req.extensions().get::<ClientIp>().map(ToString::to_string),
+            headers = ?self.redactor.headers(req.headers()),
+        );
+        // 接上网关传来的链路，而不是每个请求新开一条
+        set_parent_from_headers(&span, req.headers());
+        span
+    }
+}
+
+/// 将 request id 放入任务上下文，供 `AppError` 写入错误响应
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdScopeLayer;
+
+impl<S> Layer<S> for RequestIdScopeLayer {
+    type Service = RequestIdScope<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdScope { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestIdScope<S> {
+    inner: S,
+}
+
+impl<S, ReqBody> Service<Request<ReqBody>> for RequestIdScope<S>
+where
+    S: Service<Request<ReqBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let id = get_request_id(&req).unwrap_or_default().to_string();
+        let fut = self.inner.call(req);
+        Box::pin(request_id::scope(id, fut))
+    }
+}
@@ -0,0 +1,2 @@
+-- Remove the user role
+ALTER TABLE users DROP COLUMN IF EXISTS role;
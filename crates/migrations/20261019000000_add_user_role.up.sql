@@ -0,0 +1,2 @@
+-- User role, `admin` users can call the /admin routes
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'user';
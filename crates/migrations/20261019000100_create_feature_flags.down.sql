@@ -0,0 +1,2 @@
+-- Remove the feature flag overrides
+DROP TABLE IF EXISTS feature_flags;
@@ -0,0 +1,10 @@
+-- Runtime overrides for the feature flags defined under [features.flags] in the config
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    percentage SMALLINT CHECK (percentage BETWEEN 0 AND 100),
+    users UUID[] NOT NULL DEFAULT '{}',
+    tenants TEXT[] NOT NULL DEFAULT '{}',
+    updated_by TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -1,32 +1,164 @@
-use std::time::Duration;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
-use configure::CONFIG;
-use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
-use tokio::sync::OnceCell;
-use tracing::info;
+use configure::database::DatabaseConfig;
+use log::LevelFilter;
+use metrics::{counter, gauge, histogram};
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions, PgSslMode},
+    ConnectOptions, Pool, Postgres,
+};
+use tracing::{info, warn};
 
 pub type PgPool = Pool<Postgres>;
 
-pub static DB_POOL: OnceCell<PgPool> = OnceCell::const_new();
-
-pub async fn init_database() {
+/// 按配置创建连接池
+///
+/// 迁移通过 `api migrate up` 单独执行，见 `migrate` 模块
+pub async fn connect(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
     info!("connecting to postgres");
-    let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .acquire_timeout(Duration::from_secs(3))
-        .idle_timeout(Duration::from_secs(50))
-        .connect(&CONFIG.database.get_url())
-        .await
-        .expect("connect database error");
-
-    // Run migrations (requires `migrations/` at workspace root)
-    // sqlx::migrate!("../migrations")
-    //     .run(&pool)
-    //     .await
-    //     .expect("migrate database error");
-    DB_POOL.set(pool).unwrap();
+    pool_options(config).connect_with(connect_options(config)?).await
+}
+
+/// 按配置创建连接池，第一次使用时才建立连接
+pub fn connect_lazy(config: &DatabaseConfig) -> Result<PgPool, sqlx::Error> {
+    Ok(pool_options(config).connect_lazy_with(connect_options(config)?))
+}
+
+/// 连接池参数
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .min_connections(config.min_connections)
+        .max_connections(config.max_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(config.idle_timeout_secs))
+        .max_lifetime(Duration::from_secs(config.max_lifetime_secs))
+}
+
+/// 连接参数，包括 TLS、应用名、`statement_timeout` 以及语句日志和慢查询日志的级别
+///
+/// 配置检查已经校验过 URL 和日志级别，这里仍返回错误而不是 panic
+fn connect_options(config: &DatabaseConfig) -> Result<PgConnectOptions, sqlx::Error> {
+    let level = |name: &str| {
+        LevelFilter::from_str(name).map_err(|_| {
+            sqlx::Error::Configuration(format!("invalid log level for database: {name}").into())
+        })
+    };
+    // 单独配置的字段直接设置，避免拼接 URL 时的转义问题
+    let mut options = match &config.url {
+        Some(url) => PgConnectOptions::from_str(url.expose())?,
+        None => PgConnectOptions::new()
+            .host(&config.host)
+            .port(config.port)
+            .username(&config.username)
+            .password(config.password.expose())
+            .database(&config.database_name),
+    };
+    if let Some(mode) = config.ssl_mode {
+        options = options.ssl_mode(PgSslMode::from_str(mode.as_str())?);
+    }
+    if let Some(cert) = &config.ssl_root_cert {
+        options = options.ssl_root_cert(cert);
+    }
+    // 作为连接的启动参数设置，不需要在每个新连接上额外执行一条语句
+    if let Some(timeout_ms) = config.statement_timeout_ms {
+        options = options.options([("statement_timeout", timeout_ms)]);
+    }
+    Ok(options
+        .application_name(&config.application_name)
+        .log_statements(level(&config.log_statements)?)
+        .log_slow_statements(
+            level(&config.log_slow_statements)?,
+            Duration::from_millis(config.slow_statement_threshold_ms),
+        ))
+}
+
+/// 定期采集连接池指标：连接数、空闲连接数，以及从连接池获取一个探测连接的耗时
+///
+/// 探测耗时只反映采样时刻连接池的拥堵程度，不是真实请求等待连接的时间
+pub fn spawn_pool_metrics(pool: PgPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            gauge!("db_pool_connections", "state" => "total").set(pool.size() as f64);
+            gauge!("db_pool_connections", "state" => "idle").set(pool.num_idle() as f64);
+            gauge!("db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+
+            let start = Instant::now();
+            match pool.acquire().await {
+                Ok(conn) => {
+                    histogram!("db_pool_probe_acquire_seconds")
+                        .record(start.elapsed().as_secs_f64());
+                    drop(conn);
+                }
+                Err(e) => {
+                    counter!("db_pool_probe_errors_total").increment(1);
+                    warn!("failed to acquire connection for pool metrics: {}", e);
+                }
+            }
+        }
+    });
 }
 
-pub fn get_db_pool() -> &'static PgPool {
-    DB_POOL.get().expect("Database pool is not initialized")
+#[cfg(test)]
+mod tests {
+    use configure::{database::SslMode, secret::Secret, AppConfig};
+
+    use super::*;
+
+    fn database() -> DatabaseConfig {
+        AppConfig::read().unwrap().database
+    }
+
+    #[test]
+    fn builds_options_from_separate_fields() {
+        let mut config = database();
+        config.url = None;
+        config.password = Secret::new("p@ss/word");
+        config.ssl_mode = Some(SslMode::Require);
+        config.statement_timeout_ms = Some(1500);
+        config.slow_statement_threshold_ms = 250;
+        config.log_slow_statements = "error".to_string();
+        let options = connect_options(&config).unwrap();
+
+        assert_eq!(options.get_host(), config.host);
+        assert_eq!(options.get_database(), Some(config.database_name.as_str()));
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::Require));
+        assert_eq!(options.get_application_name(), Some(config.application_name.as_str()));
+        assert_eq!(options.get_options(), Some("-c statement_timeout=1500"));
+        let debug = format!("{options:?}");
+        assert!(debug.contains("slow_statements_level: Error"), "{debug}");
+        assert!(debug.contains("slow_statements_duration: 250ms"), "{debug}");
+    }
+
+    #[test]
+    fn separate_fields_override_the_url() {
+        let mut config = database();
+        config.url = Some(Secret::new("postgres://u:p@db.internal:6543/orders?sslmode=disable"));
+        config.ssl_mode = Some(SslMode::VerifyFull);
+        config.statement_timeout_ms = None;
+        let options = connect_options(&config).unwrap();
+
+        assert_eq!(options.get_host(), "db.internal");
+        assert_eq!(options.get_port(), 6543);
+        assert_eq!(options.get_database(), Some("orders"));
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::VerifyFull));
+        assert_eq!(options.get_options(), None);
+    }
+
+    #[test]
+    fn invalid_settings_are_errors() {
+        let mut config = database();
+        config.log_statements = "loud".to_string();
+        let error = connect_options(&config).unwrap_err().to_string();
+        assert!(error.contains("invalid log level for database: loud"), "{error}");
+
+        let mut config = database();
+        config.url = Some(Secret::new("postgres://u:p@db:notaport/orders"));
+        assert!(connect_options(&config).is_err());
+        assert!(connect_lazy(&config).is_err());
+    }
 }
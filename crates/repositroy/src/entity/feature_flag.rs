@@ -0,0 +1,87 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{self, PgPool};
+use uuid::Uuid;
+
+use crate::trace::traced;
+
+/// 功能开关的运行时覆盖，存在时优先于配置中的默认规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlagOverride {
+    pub name: String,
+    pub enabled: bool,
+    pub percentage: Option<i16>,
+    pub users: Vec<Uuid>,
+    pub tenants: Vec<String>,
+    pub updated_by: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn list_flag_overrides(pool: &PgPool) -> Result<Vec<FeatureFlagOverride>> {
+    let rows = traced(
+        "SELECT",
+        "feature_flags",
+        sqlx::query_as!(
+            FeatureFlagOverride,
+            r#"SELECT name, enabled, percentage, users, tenants, updated_by, updated_at
+           FROM feature_flags
+           ORDER BY name"#
+        )
+        .fetch_all(pool),
+    )
+    .await?;
+    Ok(rows)
+}
+
+pub async fn upsert_flag_override(
+    pool: &PgPool,
+    name: &str,
+    enabled: bool,
+    percentage: Option<i16>,
+    users: &[Uuid],
+    tenants: &[String],
+    updated_by: &str,
+) -> Result<FeatureFlagOverride> {
+    let rec = traced(
+        "INSERT",
+        "feature_flags",
+        sqlx::query_as!(
+            FeatureFlagOverride,
+            r#"INSERT INTO feature_flags (name, enabled, percentage, users, tenants, updated_by)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           ON CONFLICT (name) DO UPDATE
+           SET enabled = EXCLUDED.enabled, percentage = EXCLUDED.percentage,
+               users = EXCLUDED.users, tenants = EXCLUDED.tenants,
+               updated_by = EXCLUDED.updated_by, updated_at = now()
+           RETURNING name, enabled, percentage, users, tenants, updated_by, updated_at"#,
+            name,
+            enabled,
+            percentage,
+            users,
+            tenants,
+            updated_by
+        )
+        .fetch_one(pool),
+    )
+    .await?;
+    Ok(rec)
+}
+
+pub async fn delete_flag_override(
+    pool: &PgPool,
+    name: &str,
+) -> Result<Option<FeatureFlagOverride>> {
+    let rec = traced(
+        "DELETE",
+        "feature_flags",
+        sqlx::query_as!(
+            FeatureFlagOverride,
+            r#"DELETE FROM feature_flags WHERE name = $1
+           RETURNING name, enabled, percentage, users, tenants, updated_by, updated_at"#,
+            name
+        )
+        .fetch_optional(pool),
+    )
+    .await?;
+    Ok(rec)
+}
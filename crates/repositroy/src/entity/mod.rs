@@ -1,3 +1,4 @@
+pub mod feature_flag;
 pub mod user;
 
 pub use user::User;
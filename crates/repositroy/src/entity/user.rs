@@ -1,73 +1,138 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::{self, PgPool};
+
+use crate::trace::traced;
+
 // Data model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: uuid::Uuid,
     pub email: String,
     pub name: String,
+    /// 用户角色，默认为 `user`
+    #[serde(default)]
+    pub role: String,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 // Queries
 pub async fn create_user(pool: &PgPool, email: &str, name: &str) -> Result<User> {
-    let rec = sqlx::query_as!(
-        User,
-        r#"INSERT INTO users (email, name)
+    let rec = traced(
+        "INSERT",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"INSERT INTO users (email, name)
            VALUES ($1, $2)
-           RETURNING id, email, name, created_at"#,
-        email,
-        name
+           RETURNING id, email, name, role, created_at"#,
+            email,
+            name
+        )
+        .fetch_one(pool),
     )
-    .fetch_one(pool)
     .await?;
     Ok(rec)
 }
 
 pub async fn update_user(pool: &PgPool, user: &User) -> Result<User> {
-    let rec = sqlx::query_as!(
-        User,
-        r#"UPDATE users
+    let rec = traced(
+        "UPDATE",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"UPDATE users
         SET email = $1, name = $2
            where id = $3
-           RETURNING id, email, name, created_at"#,
-        user.email,
-        user.name,
-        user.id
+           RETURNING id, email, name, role, created_at"#,
+            user.email,
+            user.name,
+            user.id
+        )
+        .fetch_one(pool),
     )
-    .fetch_one(pool)
     .await?;
     Ok(rec)
 }
 
 pub async fn get_user(pool: &PgPool, id: uuid::Uuid) -> Result<Option<User>> {
-    let rec =
-        sqlx::query_as!(User, r#"SELECT id, email, name, created_at FROM users WHERE id = $1"#, id)
-            .fetch_optional(pool)
-            .await?;
+    let rec = traced(
+        "SELECT",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"SELECT id, email, name, role, created_at FROM users WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(pool),
+    )
+    .await?;
+    Ok(rec)
+}
+
+pub async fn get_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>> {
+    let rec = traced(
+        "SELECT",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"SELECT id, email, name, role, created_at FROM users WHERE email = $1"#,
+            email
+        )
+        .fetch_optional(pool),
+    )
+    .await?;
+    Ok(rec)
+}
+
+/// 按邮箱创建用户并设置角色，用户已存在时只更新角色
+pub async fn upsert_user_role(pool: &PgPool, email: &str, name: &str, role: &str) -> Result<User> {
+    let rec = traced(
+        "INSERT",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"INSERT INTO users (email, name, role)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (email) DO UPDATE SET role = EXCLUDED.role
+           RETURNING id, email, name, role, created_at"#,
+            email,
+            name,
+            role
+        )
+        .fetch_one(pool),
+    )
+    .await?;
     Ok(rec)
 }
 
 pub async fn list_users(pool: &PgPool, limit: i64, offset: i64) -> Result<Vec<User>> {
-    let rows = sqlx::query_as!(
-        User,
-        r#"SELECT id, email, name, created_at
+    let rows = traced(
+        "SELECT",
+        "users",
+        sqlx::query_as!(
+            User,
+            r#"SELECT id, email, name, role, created_at
            FROM users
            ORDER BY created_at DESC
            LIMIT $1 OFFSET $2"#,
-        limit,
-        offset
+            limit,
+            offset
+        )
+        .fetch_all(pool),
     )
-    .fetch_all(pool)
     .await?;
     Ok(rows)
 }
 
 pub async fn del_user(pool: &PgPool, id: uuid::Uuid) -> Result<Option<User>> {
-    let user = sqlx::query_as!(User, r#"DELETE FROM users WHERE id = $1 RETURNING *"#, id)
-        .fetch_optional(pool)
-        .await?;
+    let user = traced(
+        "DELETE",
+        "users",
+        sqlx::query_as!(User, r#"DELETE FROM users WHERE id = $1 RETURNING *"#, id)
+            .fetch_optional(pool),
+    )
+    .await?;
 
     Ok(user)
 }
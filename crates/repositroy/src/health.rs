@@ -0,0 +1,40 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// 执行 `SELECT 1` 检查数据库是否可用
+pub async fn ping(pool: &PgPool) -> Result<()> {
+    sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(pool).await?;
+    Ok(())
+}
+
+/// 迁移记录的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    /// 还没有执行过迁移
+    NotInitialized,
+    /// 全部迁移都执行成功
+    Applied { count: i64 },
+    /// 存在执行失败(未完成)的迁移
+    Dirty { failed: i64 },
+}
+
+/// 读取 `_sqlx_migrations` 表检查迁移状态
+pub async fn migration_state(pool: &PgPool) -> Result<MigrationState> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        return Ok(MigrationState::NotInitialized);
+    }
+
+    let (count, failed): (i64, i64) = sqlx::query_as(
+        "SELECT count(*), count(*) FILTER (WHERE NOT success) FROM _sqlx_migrations",
+    )
+    .fetch_one(pool)
+    .await?;
+    if failed > 0 {
+        Ok(MigrationState::Dirty { failed })
+    } else {
+        Ok(MigrationState::Applied { count })
+    }
+}
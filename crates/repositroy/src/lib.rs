@@ -1,5 +1,8 @@
 pub mod db;
 pub mod entity;
+pub mod health;
+pub mod migrate;
+pub mod trace;
 
-pub use db::{get_db_pool, init_database, PgPool};
+pub use db::{connect, connect_lazy, spawn_pool_metrics, PgPool};
 pub use entity::*;
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use sqlx::{migrate::Migrator, PgPool};
+
+/// 编译时嵌入的迁移文件，位于 `crates/migrations`
+pub static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
+
+/// 单个迁移的执行状态
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// 已执行时为执行是否成功，未执行为 None
+    pub success: Option<bool>,
+}
+
+impl MigrationStatus {
+    pub fn is_pending(&self) -> bool {
+        self.success.is_none()
+    }
+}
+
+/// 执行所有未执行的迁移
+pub async fn run(pool: &PgPool) -> Result<()> {
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+/// 回滚最近执行的 `steps` 个迁移，返回被回滚的版本号
+pub async fn undo(pool: &PgPool, steps: usize) -> Result<Vec<i64>> {
+    let applied: Vec<i64> =
+        status(pool).await?.into_iter().filter(|m| !m.is_pending()).map(|m| m.version).collect();
+    let (target, reverted) = undo_plan(applied, steps);
+    MIGRATOR.undo(pool, target).await?;
+    Ok(reverted)
+}
+
+/// 回滚 `steps` 个迁移时传给 [`Migrator::undo`] 的目标版本(回滚所有更新的版本)，以及按回滚顺序排列的版本号
+fn undo_plan(mut applied: Vec<i64>, steps: usize) -> (i64, Vec<i64>) {
+    applied.sort_unstable();
+    let keep = applied.len().saturating_sub(steps);
+    let target = if keep == 0 { 0 } else { applied[keep - 1] };
+    (target, applied.split_off(keep).into_iter().rev().collect())
+}
+
+/// 嵌入的迁移以及数据库中已执行的迁移，按版本号排序
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    let mut applied: HashMap<i64, (String, bool)> = if exists {
+        sqlx::query_as::<_, (i64, String, bool)>(
+            "SELECT version, description, success FROM _sqlx_migrations",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(version, description, success)| (version, (description, success)))
+        .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut migrations: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            success: applied.remove(&m.version).map(|(_, success)| success),
+        })
+        .collect();
+    // 数据库中有但当前代码中没有的迁移
+    migrations.extend(applied.into_iter().map(|(version, (description, success))| {
+        MigrationStatus { version, description, success: Some(success) }
+    }));
+    migrations.sort_by_key(|m| m.version);
+    Ok(migrations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_plan_reverts_latest_first() {
+        let applied = vec![20240103, 20240101, 20240102];
+        assert_eq!(undo_plan(applied.clone(), 1), (20240102, vec![20240103]));
+        assert_eq!(undo_plan(applied, 2), (20240101, vec![20240103, 20240102]));
+    }
+
+    #[test]
+    fn undo_plan_reverts_everything_when_steps_exceed_applied() {
+        let applied = vec![20240101, 20240102];
+        assert_eq!(undo_plan(applied.clone(), 2), (0, vec![20240102, 20240101]));
+        assert_eq!(undo_plan(applied, 5), (0, vec![20240102, 20240101]));
+        assert_eq!(undo_plan(Vec::new(), 1), (0, Vec::new()));
+    }
+
+    #[test]
+    fn undo_plan_with_zero_steps_reverts_nothing() {
+        assert_eq!(undo_plan(vec![20240101, 20240102], 0), (20240102, Vec::new()));
+    }
+}
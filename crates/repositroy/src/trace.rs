@@ -0,0 +1,66 @@
+use std::future::Future;
+
+use tracing::{field, info_span, Instrument};
+
+use crate::entity::{feature_flag::FeatureFlagOverride, User};
+
+/// 查询结果包含的行数，记录到 span 的 `db.response.returned_rows` 字段
+pub trait RowCount {
+    fn row_count(&self) -> usize;
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> RowCount for Option<T> {
+    fn row_count(&self) -> usize {
+        usize::from(self.is_some())
+    }
+}
+
+impl RowCount for User {
+    fn row_count(&self) -> usize {
+        1
+    }
+}
+
+impl RowCount for FeatureFlagOverride {
+    fn row_count(&self) -> usize {
+        1
+    }
+}
+
+/// 在符合 OpenTelemetry 数据库语义约定的 span 中执行查询
+///
+/// `operation` 为 SQL 操作(SELECT / INSERT / ...)，`table` 为操作的表名
+pub async fn traced<T, F>(operation: &'static str, table: &'static str, query: F) -> sqlx::Result<T>
+where
+    T: RowCount,
+    F: Future<Output = sqlx::Result<T>>,
+{
+    let span = info_span!(
+        "db.query",
+        otel.name = format!("{operation} {table}"),
+        otel.kind = "client",
+        otel.status_code = field::Empty,
+        db.system = "postgresql",
+        db.operation = operation,
+        db.sql.table = table,
+        db.response.returned_rows = field::Empty,
+        error = field::Empty,
+    );
+    let result = query.instrument(span.clone()).await;
+    match &result {
+        Ok(rows) => {
+            span.record("db.response.returned_rows", rows.row_count());
+        }
+        Err(e) => {
+            span.record("otel.status_code", "ERROR");
+            span.record("error", field::display(e));
+        }
+    }
+    result
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use configure::{
+    error::{AppError, ErrorCode},
+    features::{FlagContext, FlagRule},
+    reload::LiveConfig,
+};
+use repositroy::{
+    entity::feature_flag::{
+        delete_flag_override, list_flag_overrides, upsert_flag_override, FeatureFlagOverride,
+    },
+    PgPool,
+};
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+type Overrides = Arc<HashMap<String, FeatureFlagOverride>>;
+
+/// 功能开关：配置中的定义和默认规则，加上数据库中的运行时覆盖
+///
+/// 运行时覆盖按 `features.cache_ttl_secs` 缓存，过期后先返回旧值并在后台刷新，
+/// 多个实例之间的修改最多延迟一个 TTL 加一次查询生效
+#[derive(Clone)]
+pub struct FeatureFlags {
+    pool: PgPool,
+    config: LiveConfig,
+    cache: Arc<RwLock<Option<CachedOverrides>>>,
+    /// 同一时间只有一个请求查询数据库
+    refreshing: Arc<Mutex<()>>,
+}
+
+#[derive(Clone)]
+struct CachedOverrides {
+    loaded_at: Instant,
+    overrides: Overrides,
+}
+
+/// 一个功能开关的状态，用于管理接口
+#[derive(Debug, Serialize)]
+pub struct FlagStatus {
+    pub name: String,
+    pub description: String,
+    /// 配置中的默认规则
+    pub default: FlagRule,
+    /// 数据库中的运行时覆盖，存在时优先于默认规则
+    #[serde(rename = "override")]
+    pub overridden: Option<FeatureFlagOverride>,
+}
+
+impl FeatureFlags {
+    pub fn new(pool: PgPool, config: LiveConfig) -> Self {
+        Self {
+            pool,
+            config,
+            cache: Arc::new(RwLock::new(None)),
+            refreshing: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// 配置中定义的开关里对指定用户/租户开启的开关名
+    pub async fn enabled(&self, ctx: &FlagContext) -> Vec<String> {
+        let overrides = self.overrides().await;
+        let config = self.config.load();
+        config
+            .features
+            .flags
+            .iter()
+            .filter(|(name, flag)| match overrides.get(*name) {
+                Some(overridden) => rule_of(overridden).evaluate(name, ctx),
+                None => flag.rule().evaluate(name, ctx),
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// 开关是否对指定用户/租户开启，未在配置中定义的开关始终关闭
+    pub async fn is_enabled(&self, name: &str, ctx: &FlagContext) -> bool {
+        let default = match self.config.load().features.flags.get(name) {
+            Some(flag) => flag.rule(),
+            None => {
+                debug!(flag = name, "feature flag is not defined, treated as disabled");
+                return false;
+            }
+        };
+        let overrides = self.overrides().await;
+        match overrides.get(name) {
+            Some(overridden) => rule_of(overridden).evaluate(name, ctx),
+            None => default.evaluate(name, ctx),
+        }
+    }
+
+    /// 配置中定义的所有开关及其运行时覆盖，直接读取数据库并刷新缓存
+    pub async fn list(&self) -> Result<Vec<FlagStatus>, AppError> {
+        let overrides = self.refresh().await?;
+        let config = self.config.load();
+        Ok(config
+            .features
+            .flags
+            .iter()
+            .map(|(name, flag)| FlagStatus {
+                name: name.clone(),
+                description: flag.description.clone(),
+                default: flag.rule(),
+                overridden: overrides.get(name).cloned(),
+            })
+            .collect())
+    }
+
+    /// 设置开关的运行时覆盖
+    pub async fn set(
+        &self,
+        name: &str,
+        rule: &FlagRule,
+        updated_by: &str,
+    ) -> Result<FlagStatus, AppError> {
+        self.ensure_defined(name)?;
+        let percentage = rule.percentage.map(i16::from);
+        if percentage.is_some_and(|p| p > 100) {
+            return Err(AppError::coded(
+                ErrorCode::InvalidFeatureFlag,
+                "percentage must be between 0 and 100",
+            ));
+        }
+        upsert_flag_override(
+            &self.pool,
+            name,
+            rule.enabled,
+            percentage,
+            &rule.users,
+            &rule.tenants,
+            updated_by,
+        )
+        .await?;
+        self.status(name).await
+    }
+
+    /// 删除开关的运行时覆盖，恢复为配置中的默认规则
+    pub async fn reset(&self, name: &str) -> Result<FlagStatus, AppError> {
+        self.ensure_defined(name)?;
+        delete_flag_override(&self.pool, name).await?;
+        self.status(name).await
+    }
+
+    async fn status(&self, name: &str) -> Result<FlagStatus, AppError> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|status| status.name == name)
+            .ok_or_else(|| not_found(name))
+    }
+
+    fn ensure_defined(&self, name: &str) -> Result<(), AppError> {
+        if self.config.load().features.flags.contains_key(name) {
+            Ok(())
+        } else {
+            Err(not_found(name))
+        }
+    }
+
+    /// 缓存的运行时覆盖，过期时返回旧值并在后台刷新，只有第一次读取需要等待数据库
+    async fn overrides(&self) -> Overrides {
+        let ttl = Duration::from_secs(self.config.load().features.cache_ttl_secs);
+        if let Some(cached) = self.cached() {
+            if cached.loaded_at.elapsed() >= ttl {
+                self.spawn_reload();
+            }
+            return cached.overrides;
+        }
+        let _refreshing = self.refreshing.lock().await;
+        // 等待期间其他请求可能已经读取完成
+        match self.cached() {
+            Some(cached) => cached.overrides,
+            None => self.reload().await,
+        }
+    }
+
+    fn cached(&self) -> Option<CachedOverrides> {
+        self.cache.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn store(&self, overrides: Overrides) {
+        let cached = CachedOverrides { loaded_at: Instant::now(), overrides };
+        *self.cache.write().unwrap_or_else(|e| e.into_inner()) = Some(cached);
+    }
+
+    /// 已经有刷新在进行时什么也不做
+    fn spawn_reload(&self) {
+        let Ok(refreshing) = self.refreshing.clone().try_lock_owned() else {
+            return;
+        };
+        let flags = self.clone();
+        tokio::spawn(async move {
+            let _refreshing = refreshing;
+            flags.reload().await;
+        });
+    }
+
+    /// 重新读取运行时覆盖，读取失败时继续使用旧的缓存
+    async fn reload(&self) -> Overrides {
+        match list_flag_overrides(&self.pool).await {
+            Ok(rows) => {
+                let overrides = Arc::new(index(rows));
+                self.store(overrides.clone());
+                overrides
+            }
+            Err(e) => {
+                warn!("failed to load feature flag overrides, using cached values: {}", e);
+                // 避免数据库故障时每次判断都去查询
+                let overrides = self.cached().map(|cached| cached.overrides).unwrap_or_default();
+                self.store(overrides.clone());
+                overrides
+            }
+        }
+    }
+
+    async fn refresh(&self) -> Result<Overrides, AppError> {
+        let overrides = Arc::new(index(list_flag_overrides(&self.pool).await?));
+        self.store(overrides.clone());
+        Ok(overrides)
+    }
+}
+
+fn index(rows: Vec<FeatureFlagOverride>) -> HashMap<String, FeatureFlagOverride> {
+    rows.into_iter().map(|row| (row.name.clone(), row)).collect()
+}
+
+fn rule_of(overridden: &FeatureFlagOverride) -> FlagRule {
+    FlagRule {
+        enabled: overridden.enabled,
+        percentage: overridden.percentage.and_then(|p| u8::try_from(p).ok()),
+        users: overridden.users.clone(),
+        tenants: overridden.tenants.clone(),
+    }
+}
+
+fn not_found(name: &str) -> AppError {
+    AppError::coded(ErrorCode::FeatureFlagNotFound, format!("Feature flag {name} is not defined"))
+}
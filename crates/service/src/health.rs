@@ -0,0 +1,177 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use repositroy::{
+    health::{migration_state, ping, MigrationState},
+    migrate,
+};
+use serde::Serialize;
+use tracing::warn;
+
+use super::*;
+
+/// 就绪状态，开始优雅停机后立即变为未就绪，让负载均衡先摘掉流量
+#[derive(Debug, Default, Clone)]
+pub struct Readiness {
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    pub fn mark_shutting_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+}
+
+/// 隐藏内部错误细节时失败检查的说明
+const MASKED_DETAIL: &str = "check failed, see server logs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+}
+
+/// 单项检查的结果
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub status: HealthStatus,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<&'static str, CheckResult>,
+}
+
+impl HealthReport {
+    fn new(checks: BTreeMap<&'static str, CheckResult>) -> Self {
+        let status = if checks.values().all(|c| c.status == HealthStatus::Up) {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        };
+        Self { status, checks }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.status == HealthStatus::Up
+    }
+
+    /// 隐藏失败检查的细节，例如数据库的错误信息，细节已经记录在日志中
+    pub fn masked(mut self) -> Self {
+        for check in self.checks.values_mut() {
+            if check.status == HealthStatus::Down {
+                check.detail = Some(MASKED_DETAIL.to_string());
+            }
+        }
+        self
+    }
+}
+
+impl Services {
+    /// 存活检查，只要进程能处理请求就认为存活
+    pub fn liveness(&self) -> HealthReport {
+        let process = CheckResult { status: HealthStatus::Up, latency_ms: 0.0, detail: None };
+        HealthReport::new(BTreeMap::from([("process", process)]))
+    }
+
+    /// 就绪检查：未处于停机状态、数据库可用且迁移已全部成功执行
+    pub async fn readiness(&self, readiness: &Readiness, timeout: Duration) -> HealthReport {
+        let mut checks = BTreeMap::new();
+        if readiness.is_shutting_down() {
+            let shutdown = CheckResult {
+                status: HealthStatus::Down,
+                latency_ms: 0.0,
+                detail: Some("shutting down".to_string()),
+            };
+            checks.insert("shutdown", shutdown);
+            return HealthReport::new(checks);
+        }
+
+        let database = timed_check("database", timeout, async {
+            ping(&self.pool).await?;
+            Ok(None)
+        })
+        .await;
+        checks.insert("database", database);
+
+        let migrations = timed_check("migrations", timeout, async {
+            if let MigrationState::Dirty { failed } = migration_state(&self.pool).await? {
+                return Err(anyhow::anyhow!("{failed} migration(s) failed or incomplete"));
+            }
+            let migrations = migrate::status(&self.pool).await?;
+            let pending = migrations.iter().filter(|m| m.is_pending()).count();
+            if pending > 0 {
+                return Err(anyhow::anyhow!(
+                    "{pending} pending migration(s), run `api migrate up`"
+                ));
+            }
+            Ok(Some(format!("{} applied", migrations.len())))
+        })
+        .await;
+        checks.insert("migrations", migrations);
+
+        HealthReport::new(checks)
+    }
+}
+
+/// 在超时时间内执行检查并记录耗时，失败时记录警告日志
+async fn timed_check<F>(name: &str, timeout: Duration, check: F) -> CheckResult
+where
+    F: Future<Output = anyhow::Result<Option<String>>>,
+{
+    let start = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+    match result {
+        Ok(Ok(detail)) => CheckResult { status: HealthStatus::Up, latency_ms, detail },
+        Ok(Err(e)) => {
+            warn!(check = name, "readiness check failed: {:#}", e);
+            CheckResult { status: HealthStatus::Down, latency_ms, detail: Some(e.to_string()) }
+        }
+        Err(_) => {
+            warn!(check = name, "readiness check timed out after {}ms", timeout.as_millis());
+            CheckResult {
+                status: HealthStatus::Down,
+                latency_ms,
+                detail: Some(format!("timed out after {}ms", timeout.as_millis())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: HealthStatus, detail: &str) -> CheckResult {
+        CheckResult { status, latency_ms: 1.0, detail: Some(detail.to_string()) }
+    }
+
+    #[test]
+    fn masked_hides_only_failed_details() {
+        let mut checks = BTreeMap::new();
+        checks.insert("database", check(HealthStatus::Down, "password authentication failed"));
+        checks.insert("migrations", check(HealthStatus::Up, "3 applied"));
+        let report = HealthReport::new(checks).masked();
+
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.checks["database"].detail.as_deref(), Some(MASKED_DETAIL));
+        assert_eq!(report.checks["migrations"].detail.as_deref(), Some("3 applied"));
+    }
+}
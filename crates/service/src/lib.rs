@@ -1,4 +1,9 @@
+pub mod feature_flags;
+pub mod health;
 pub mod user_service;
+use configure::{log_filter::LogFilterHandle, maintenance::Maintenance, reload::LiveConfig};
+use feature_flags::FeatureFlags;
+use health::Readiness;
 use repositroy::PgPool;
 
 #[derive(Clone)]
@@ -8,5 +13,10 @@ pub struct Services {
 
 #[derive(Clone)]
 pub struct AppState {
+    pub config: LiveConfig,
     pub services: Services,
+    pub maintenance: Maintenance,
+    pub log_filter: LogFilterHandle,
+    pub readiness: Readiness,
+    pub features: FeatureFlags,
 }
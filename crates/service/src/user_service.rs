@@ -1,6 +1,10 @@
 use anyhow::Result;
+use configure::jwt::ADMIN_ROLE;
 use repositroy::{
-    entity::user::{create_user, del_user, get_user, list_users, update_user, User},
+    entity::user::{
+        create_user, del_user, get_user, get_user_by_email, list_users, update_user,
+        upsert_user_role, User,
+    },
     PgPool,
 };
 use tracing::instrument;
@@ -8,17 +12,18 @@ use uuid::Uuid;
 
 use super::*;
 
+// 邮箱和姓名属于个人信息，不记录到 span 中
 impl Services {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
 
-    #[instrument(skip(self))]
+    #[instrument(skip(self, email, name))]
     pub async fn create_user(&self, email: &str, name: &str) -> Result<User> {
         create_user(&self.pool, email, name).await
     }
 
-    #[instrument(skip(self))]
+    #[instrument(skip(self, user), fields(user_id = %user.id))]
     pub async fn update_user(&self, user: &User) -> Result<User> {
         update_user(&self.pool, user).await
     }
@@ -28,6 +33,17 @@ impl Services {
         get_user(&self.pool, id).await
     }
 
+    #[instrument(skip(self, email))]
+    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        get_user_by_email(&self.pool, email).await
+    }
+
+    /// 创建管理员，邮箱已存在时把该用户提升为管理员
+    #[instrument(skip(self, email, name))]
+    pub async fn create_admin(&self, email: &str, name: &str) -> Result<User> {
+        upsert_user_role(&self.pool, email, name, ADMIN_ROLE).await
+    }
+
     #[instrument(skip(self))]
     pub async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>> {
         list_users(&self.pool, limit, offset).await
@@ -38,3 +54,67 @@ impl Services {
         del_user(&self.pool, id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use configure::AppConfig;
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    use super::*;
+
+    const EMAIL: &str = "someone@example.com";
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn emails_are_not_logged() {
+        let output = Output::default();
+        let writer = output.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .with_span_events(FmtSpan::NEW)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // 数据库不可达，查询失败，但 span 已经创建
+        let mut config = AppConfig::read().unwrap().database;
+        config.url = None;
+        config.port = 1;
+        config.acquire_timeout_secs = 1;
+        let services = Services::new(repositroy::connect_lazy(&config).unwrap());
+        let user = User {
+            id: Uuid::new_v4(),
+            email: EMAIL.to_string(),
+            name: "someone".to_string(),
+            role: "user".to_string(),
+            created_at: None,
+        };
+        assert!(services.update_user(&user).await.is_err());
+        assert!(services.create_user(EMAIL, "someone").await.is_err());
+        assert!(services.create_admin(EMAIL, "someone").await.is_err());
+        assert!(services.get_user_by_email(EMAIL).await.is_err());
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains(&format!("user_id={}", user.id)), "{output}");
+        assert!(!output.contains(EMAIL), "{output}");
+    }
+}